@@ -0,0 +1,159 @@
+//! Classic CAN data frame as it appears on the bus, bit by bit, for software PHYs and decoder testing.
+//! `false` is dominant (0) and `true` is recessive (1) level.
+use crate::{Frame, FrameId, Error};
+
+const CRC15_POLY: u16 = 0x4599;
+
+/// Writes bits into out, inserting a stuff bit after 5 equal ones while stuffing is enabled
+/// and calculating CRC over all the unstuffed bits.
+struct BitWriter<'a> {
+    out: &'a mut [bool],
+    pos: usize,
+    last: bool,
+    run: u8,
+    stuffing: bool,
+    crc: u16,
+}
+
+impl<'a> BitWriter<'a> {
+    fn push_raw(&mut self, bit: bool) -> Result<(), Error> {
+        *self.out.get_mut(self.pos).ok_or(Error::BufferTooSmall)? = bit;
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn push(&mut self, bit: bool) -> Result<(), Error> {
+        if self.stuffing {
+            let crc_next = bit ^ (self.crc & (1 << 14) != 0);
+            self.crc = (self.crc << 1) & 0x7fff;
+            if crc_next {
+                self.crc ^= CRC15_POLY;
+            }
+        }
+        self.push_raw(bit)?;
+        if !self.stuffing {
+            return Ok(());
+        }
+        if self.run > 0 && bit == self.last {
+            self.run += 1;
+        } else {
+            self.last = bit;
+            self.run = 1;
+        }
+        if self.run == 5 {
+            self.push_raw(!bit)?;
+            self.last = !bit;
+            self.run = 1;
+        }
+        Ok(())
+    }
+
+    /// Push count least significant bits of value, most significant first.
+    fn push_bits(&mut self, value: u32, count: u8) -> Result<(), Error> {
+        for i in (0..count).rev() {
+            self.push(value & (1 << i) != 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const MTU: usize> Frame<MTU> {
+    /// Serialize frame into a complete bit sequence from SOF through EOF with stuff bits and CRC,
+    /// returning the number of bits written. ACK slot is recessive, as sent by a transmitter.
+    /// Only classic frames (up to 8 bytes) can be serialized, WrongLength is returned otherwise.
+    pub fn to_bitstream(&self, out: &mut [bool]) -> Result<usize, Error> {
+        if self.data().len() > 8 {
+            return Err(Error::WrongLength);
+        }
+        let mut w = BitWriter { out, pos: 0, last: false, run: 0, stuffing: true, crc: 0 };
+        // SOF
+        w.push(false)?;
+        match self.id {
+            FrameId::Standard(sid) => {
+                w.push_bits(sid.inner() as u32, 11)?;
+                // RTR, IDE, r0
+                w.push_bits(0b000, 3)?;
+            }
+            FrameId::Extended(eid) => {
+                w.push_bits(eid.inner() >> 18, 11)?;
+                // SRR, IDE
+                w.push_bits(0b11, 2)?;
+                w.push_bits(eid.inner(), 18)?;
+                // RTR, r1, r0
+                w.push_bits(0b000, 3)?;
+            }
+        }
+        w.push_bits(self.dlc() as u32, 4)?;
+        for b in self.data() {
+            w.push_bits(*b as u32, 8)?;
+        }
+        let crc = w.crc;
+        w.push_bits(crc as u32, 15)?;
+        w.stuffing = false;
+        // CRC delimiter, ACK slot, ACK delimiter and EOF
+        for _ in 0..10 {
+            w.push(true)?;
+        }
+        Ok(w.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_all_dominant() {
+        // 34 dominant bits subject to stuffing (CRC of zeros is zero as well), stuff bit after every 5
+        let frame = Frame::<8>::new(FrameId::new_standard(0).unwrap(), &[]).unwrap();
+        let mut bits = [false; 160];
+        assert_eq!(frame.to_bitstream(&mut bits), Ok(50));
+        for (i, bit) in bits[..40].iter().enumerate() {
+            assert_eq!(*bit, i % 6 == 5);
+        }
+        assert_eq!(&bits[40..50], &[true; 10]);
+        assert_eq!(frame.to_bitstream(&mut bits[..49]), Err(Error::BufferTooSmall));
+    }
+
+    #[test]
+    fn check_stuffing() {
+        let frames = [
+            Frame::<8>::new(FrameId::new_standard(0x7FF).unwrap(), &[0xFF; 8]).unwrap(),
+            Frame::<8>::new(FrameId::new_extended(0x1234567).unwrap(), &[0x0F, 0x78, 0xC3]).unwrap(),
+            Frame::<8>::new(FrameId::new_extended(0).unwrap(), &[0; 8]).unwrap(),
+        ];
+        for frame in frames.iter() {
+            let mut bits = [false; 160];
+            let len = frame.to_bitstream(&mut bits).unwrap();
+            assert!(len <= frame.max_bit_count() as usize - 3);
+            // no 6 equal bits in a row before the CRC delimiter
+            assert!(bits[..len - 10].windows(6).all(|w| w.iter().any(|b| *b != w[0])));
+
+            // destuff and check that CRC over the whole frame including CRC field is zero
+            let mut crc = 0u16;
+            let mut run = 0;
+            let mut last = false;
+            for bit in bits[..len - 10].iter() {
+                if run == 5 {
+                    run = 1;
+                    last = *bit;
+                    continue;
+                }
+                if run > 0 && *bit == last {
+                    run += 1;
+                } else {
+                    run = 1;
+                    last = *bit;
+                }
+                let crc_next = *bit ^ (crc & (1 << 14) != 0);
+                crc = (crc << 1) & 0x7fff;
+                if crc_next {
+                    crc ^= CRC15_POLY;
+                }
+            }
+            assert_eq!(crc, 0);
+        }
+        let fd = Frame::<64>::new(FrameId::new_standard(0x123).unwrap(), &[0; 12]).unwrap();
+        assert_eq!(fd.to_bitstream(&mut [false; 256]), Err(Error::WrongLength));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::id::FrameId;
+use crate::Error;
 use core::fmt;
 use core::cmp::Ordering;
 
@@ -7,6 +8,23 @@ pub struct FrameRef<'a> {
     pub id: FrameId,
     pub data: &'a [u8]
 }
+impl<'a> FrameRef<'a> {
+    /// Copy referenced data into an owned frame, None if it doesn't fit into MTU.
+    pub fn to_owned<const MTU: usize>(&self) -> Option<Frame<MTU>> {
+        Frame::new(self.id, self.data)
+    }
+}
+impl<'a> PartialOrd for FrameRef<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Same as for [Frame] by ID, payload is only compared to break ties, to stay consistent with Eq.
+impl<'a> Ord for FrameRef<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id).then_with(|| self.data.cmp(other.data))
+    }
+}
 impl<'a> fmt::Debug for FrameRef<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if !f.sign_minus() {
@@ -49,8 +67,24 @@ impl<const MTU: usize> Frame<MTU> {
         }
     }
 
+    /// Pull exactly len bytes from an iterator, without collecting them into a temporary buffer first.
+    pub fn from_byte_iter(id: FrameId, mut bytes: impl Iterator<Item = u8>, len: usize) -> Result<Frame<MTU>, Error> {
+        if len > MTU {
+            return Err(Error::WrongLength);
+        }
+        let mut data = [0u8; MTU];
+        for b in data[..len].iter_mut() {
+            *b = bytes.next().ok_or(Error::UnexpectedEnd)?;
+        }
+        Ok(Frame {
+            id,
+            data,
+            len: len as u16
+        })
+    }
+
     pub fn new_move(id: FrameId, data: [u8; MTU], used: u16) -> Option<Frame<MTU>> {
-        if data.len() > MTU {
+        if used as usize > MTU {
             return None;
         }
         unsafe { Some(Self::new_move_unchecked(id, data, used)) }
@@ -64,10 +98,219 @@ impl<const MTU: usize> Frame<MTU> {
         }
     }
 
+    /// Prefer this over the `id` field, which may become private in the future.
+    pub fn id(&self) -> FrameId {
+        self.id
+    }
+
+    /// Retarget a frame under a different ID, payload is left as is.
+    pub fn with_id(mut self, id: FrameId) -> Frame<MTU> {
+        self.id = id;
+        self
+    }
+
+    pub fn set_id(&mut self, id: FrameId) {
+        self.id = id;
+    }
+
+    /// Reuse an existing frame for a new message, bytes beyond new length are zeroed.
+    pub fn copy_from(&mut self, id: FrameId, data: &[u8]) -> Result<(), Error> {
+        if data.len() > MTU {
+            return Err(Error::WrongLength);
+        }
+        self.data[..data.len()].copy_from_slice(data);
+        let old_len = self.len as usize;
+        if old_len > data.len() {
+            self.data[data.len()..old_len].fill(0);
+        }
+        self.id = id;
+        self.len = data.len() as u16;
+        Ok(())
+    }
+
+    /// Unused part of the payload buffer, e.g. for a DMA receive, call [commit](Frame::commit) afterwards
+    /// with the number of bytes written.
+    pub fn spare_capacity_mut(&mut self) -> &mut [u8] {
+        &mut self.data[self.len as usize..]
+    }
+
+    /// Extend used length by added bytes, written through [spare_capacity_mut](Frame::spare_capacity_mut).
+    pub fn commit(&mut self, added: u16) -> Result<(), Error> {
+        let len = self.len as usize + added as usize;
+        if len > MTU {
+            return Err(Error::WrongLength);
+        }
+        self.len = len as u16;
+        Ok(())
+    }
+
     pub fn data(&self) -> &[u8] {
         &self.data[..self.len as usize]
     }
 
+    /// 4-bit DLC field value, equal to used length for classic frames and CAN FD coded for longer ones
+    /// (rounded up to the next valid FD length: 12, 16, 20, 24, 32, 48 or 64 bytes).
+    pub fn dlc(&self) -> u8 {
+        match self.len {
+            0..=8 => self.len as u8,
+            9..=12 => 9,
+            13..=16 => 10,
+            17..=20 => 11,
+            21..=24 => 12,
+            25..=32 => 13,
+            33..=48 => 14,
+            _ => 15
+        }
+    }
+
+    /// Worst case number of bits this frame occupies on a classic CAN bus, including stuff bits
+    /// and interframe space (K. Tindell, R. Davis: "Controller Area Network (CAN) schedulability analysis").
+    pub fn max_bit_count(&self) -> u32 {
+        // bits subject to stuffing, except data field: SOF, arbitration, control and CRC
+        let g = match self.id {
+            FrameId::Standard(_) => 34,
+            FrameId::Extended(_) => 54
+        };
+        let n = self.len as u32 * 8;
+        // CRC delimiter, ACK slot, ACK delimiter, EOF and interframe space are 13 bits without stuffing
+        g + n + 13 + (g + n - 1) / 4
+    }
+
+    pub fn is_empty_payload(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copy N bytes starting at offset, None if it would read past used length.
+    pub fn data_at<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+        let end = offset.checked_add(N)?;
+        if end > self.len as usize {
+            return None;
+        }
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&self.data[offset..end]);
+        Some(bytes)
+    }
+
+    /// Reverse payload byte order in place.
+    pub fn reverse_data(&mut self) {
+        self.data[..self.len as usize].reverse();
+    }
+
+    pub fn reversed(mut self) -> Frame<MTU> {
+        self.reverse_data();
+        self
+    }
+
+    /// Replace each payload byte with f(index, byte).
+    pub fn map_data(&mut self, mut f: impl FnMut(usize, u8) -> u8) {
+        for (i, b) in self.data[..self.len as usize].iter_mut().enumerate() {
+            *b = f(i, *b);
+        }
+    }
+
+    /// XOR of all payload bytes except the one at index skip (checksum's own position).
+    pub fn xor_checksum(&self, skip: usize) -> u8 {
+        self.data().iter().enumerate()
+            .filter(|(i, _)| *i != skip)
+            .fold(0, |acc, (_, b)| acc ^ b)
+    }
+
+    /// Wrapping sum of all payload bytes except the one at index skip (checksum's own position).
+    pub fn sum_checksum(&self, skip: usize) -> u8 {
+        self.data().iter().enumerate()
+            .filter(|(i, _)| *i != skip)
+            .fold(0, |acc: u8, (_, b)| acc.wrapping_add(*b))
+    }
+
+    /// Whether byte at index skip is equal to [xor_checksum](Frame::xor_checksum), false if it's out of bounds.
+    pub fn verify_xor_checksum(&self, skip: usize) -> bool {
+        self.data().get(skip) == Some(&self.xor_checksum(skip))
+    }
+
+    /// Whether byte at index skip is equal to [sum_checksum](Frame::sum_checksum), false if it's out of bounds.
+    pub fn verify_sum_checksum(&self, skip: usize) -> bool {
+        self.data().get(skip) == Some(&self.sum_checksum(skip))
+    }
+
+    /// Write payload as lowercase hex into buf, returning the number of bytes written.
+    pub fn write_hex(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = self.data().len() * 2;
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+        for (b, chunk) in self.data().iter().zip(buf.chunks_exact_mut(2)) {
+            chunk[0] = hex_digit(b >> 4);
+            chunk[1] = hex_digit(b & 0xf);
+        }
+        Ok(len)
+    }
+
+    /// Create a frame from a hex encoded payload, both upper and lower case digits are accepted.
+    pub fn parse_payload_hex(id: FrameId, hex: &str) -> Result<Frame<MTU>, Error> {
+        let hex = hex.as_bytes();
+        if hex.len() & 1 != 0 {
+            return Err(Error::InvalidHex);
+        }
+        if hex.len() / 2 > MTU {
+            return Err(Error::WrongLength);
+        }
+        let mut data = [0u8; MTU];
+        for (b, chunk) in data.iter_mut().zip(hex.chunks_exact(2)) {
+            *b = (parse_hex_digit(chunk[0])? << 4) | parse_hex_digit(chunk[1])?;
+        }
+        Ok(Frame {
+            id,
+            data,
+            len: (hex.len() / 2) as u16
+        })
+    }
+
+    /// Write frame in can-utils candump format: `123#DEADBEEF` for standard and `12345678#DEADBEEF` for extended ID.
+    pub fn write_candump(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let (raw, id_len) = match self.id {
+            FrameId::Standard(sid) => (sid.inner() as u32, 3),
+            FrameId::Extended(eid) => (eid.inner(), 8)
+        };
+        if buf.len() < id_len + 1 + self.data().len() * 2 {
+            return Err(Error::BufferTooSmall);
+        }
+        for (i, digit) in buf[..id_len].iter_mut().enumerate() {
+            *digit = hex_digit(((raw >> ((id_len - 1 - i) * 4)) & 0xf) as u8);
+        }
+        buf[id_len] = b'#';
+        let len = id_len + 1 + self.write_hex(&mut buf[id_len + 1..])?;
+        // candump prints upper case digits
+        buf[..len].make_ascii_uppercase();
+        Ok(len)
+    }
+
+    /// Parse a frame in candump format, see [write_candump](Frame::write_candump).
+    /// ID with more than 3 hex digits is treated as extended.
+    pub fn parse_candump(line: &str) -> Result<Frame<MTU>, Error> {
+        let (id, payload) = match line.find('#') {
+            Some(pos) => (&line[..pos], &line[pos + 1..]),
+            None => return Err(Error::UnexpectedEnd)
+        };
+        if id.is_empty() || id.len() > 8 {
+            return Err(Error::InvalidId);
+        }
+        let mut raw = 0u32;
+        for digit in id.bytes() {
+            raw = (raw << 4) | parse_hex_digit(digit)? as u32;
+        }
+        let id = if id.len() <= 3 {
+            FrameId::new_standard(raw as u16)
+        } else {
+            FrameId::new_extended(raw)
+        };
+        Frame::parse_payload_hex(id.ok_or(Error::InvalidId)?, payload)
+    }
+
+    /// Convert between frame sizes, e.g. classic and FD, None if used length doesn't fit into DST.
+    pub fn resize<const DST: usize>(&self) -> Option<Frame<DST>> {
+        Frame::new(self.id, self.data())
+    }
+
     pub fn as_frame_ref(&self) -> FrameRef {
         FrameRef {
             id: self.id,
@@ -89,4 +332,182 @@ impl<const MTU: usize> Ord for Frame<MTU> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.id.cmp(&other.id)
     }
-}
\ No newline at end of file
+}
+
+pub(crate) fn hex_digit(nibble: u8) -> u8 {
+    match nibble {
+        0..=9 => b'0' + nibble,
+        _ => b'a' + nibble - 10
+    }
+}
+
+pub(crate) fn parse_hex_digit(digit: u8) -> Result<u8, Error> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(Error::InvalidHex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_dlc() {
+        let id = FrameId::new_standard(0x123).unwrap();
+        assert_eq!(Frame::<8>::new(id, &[]).unwrap().dlc(), 0);
+        assert_eq!(Frame::<8>::new(id, &[0; 8]).unwrap().dlc(), 8);
+        assert_eq!(Frame::<64>::new(id, &[0; 9]).unwrap().dlc(), 9);
+        assert_eq!(Frame::<64>::new(id, &[0; 33]).unwrap().dlc(), 14);
+        assert_eq!(Frame::<64>::new(id, &[0; 64]).unwrap().dlc(), 15);
+    }
+
+    #[test]
+    fn check_frame_ref_ord() {
+        let frame = |id, data| FrameRef { id: FrameId::new_standard(id).unwrap(), data };
+        let mut frames = [frame(0x123, &[1, 0]), frame(0x123, &[1]), frame(0x12, &[2])];
+        frames.sort();
+        assert_eq!(frames, [frame(0x12, &[2]), frame(0x123, &[1]), frame(0x123, &[1, 0])]);
+    }
+
+    #[test]
+    fn check_candump() {
+        let mut buf = [0u8; 25];
+        let frame = Frame::<8>::new(FrameId::new_standard(0x12).unwrap(), &[0xDE, 0xAD, 0xBE, 0xEF]).unwrap();
+        assert_eq!(frame.write_candump(&mut buf), Ok(12));
+        assert_eq!(&buf[..12], b"012#DEADBEEF");
+        assert_eq!(Frame::<8>::parse_candump("012#DEADBEEF"), Ok(frame));
+        assert_eq!(frame.write_candump(&mut buf[..11]), Err(Error::BufferTooSmall));
+
+        let frame = Frame::<8>::new(FrameId::new_extended(0x12345).unwrap(), &[]).unwrap();
+        assert_eq!(frame.write_candump(&mut buf), Ok(9));
+        assert_eq!(&buf[..9], b"00012345#");
+        assert_eq!(Frame::<8>::parse_candump("00012345#"), Ok(frame));
+        assert_eq!(Frame::<8>::parse_candump("12345#"), Ok(frame));
+
+        assert_eq!(Frame::<8>::parse_candump("800#00"), Err(Error::InvalidId));
+        assert_eq!(Frame::<8>::parse_candump("#00"), Err(Error::InvalidId));
+        assert_eq!(Frame::<8>::parse_candump("123456789#00"), Err(Error::InvalidId));
+        assert_eq!(Frame::<8>::parse_candump("12G#00"), Err(Error::InvalidHex));
+        assert_eq!(Frame::<8>::parse_candump("123"), Err(Error::UnexpectedEnd));
+    }
+
+    #[test]
+    fn check_new_move() {
+        let id = FrameId::new_standard(0x123).unwrap();
+        let frame = Frame::<8>::new_move(id, [1, 2, 3, 4, 5, 6, 7, 8], 3).unwrap();
+        assert_eq!(frame.data(), &[1, 2, 3]);
+        assert!(Frame::<8>::new_move(id, [0; 8], 8).is_some());
+        assert!(Frame::<8>::new_move(id, [0; 8], 9).is_none());
+    }
+
+    #[test]
+    fn check_map_data() {
+        let mut frame = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[1, 2, 3]).unwrap();
+        frame.map_data(|i, b| b * 10 + i as u8);
+        assert_eq!(frame.data(), &[10, 21, 32]);
+    }
+
+    #[test]
+    fn check_checksums() {
+        let frame = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[0x80, 0x81, 0x01, 0x02]).unwrap();
+        assert_eq!(frame.xor_checksum(3), 0x00);
+        assert_eq!(frame.sum_checksum(3), 0x02);
+        assert!(frame.verify_sum_checksum(3));
+        assert!(!frame.verify_xor_checksum(3));
+        assert!(!frame.verify_sum_checksum(4));
+        let frame = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[0x03, 0x01, 0x02]).unwrap();
+        assert!(frame.verify_xor_checksum(0));
+    }
+
+    #[test]
+    fn check_max_bit_count() {
+        assert_eq!(Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[0; 8]).unwrap().max_bit_count(), 135);
+        assert_eq!(Frame::<8>::new(FrameId::new_extended(0x123).unwrap(), &[0; 8]).unwrap().max_bit_count(), 160);
+        assert_eq!(Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[]).unwrap().max_bit_count(), 55);
+    }
+
+    #[test]
+    fn check_reverse_data() {
+        let id = FrameId::new_standard(0x123).unwrap();
+        let mut frame = Frame::<8>::new(id, &[1, 2, 3]).unwrap();
+        frame.reverse_data();
+        assert_eq!(frame.data(), &[3, 2, 1]);
+        let frame = Frame::<8>::new(id, &[1, 2, 3, 4]).unwrap().reversed();
+        assert_eq!(frame.data(), &[4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn check_hex() {
+        let id = FrameId::new_standard(0x123).unwrap();
+        let frame = Frame::<8>::new(id, &[0xde, 0xad, 0x0b, 0xef]).unwrap();
+        let mut buf = [0u8; 16];
+        assert_eq!(frame.write_hex(&mut buf), Ok(8));
+        assert_eq!(&buf[..8], b"dead0bef");
+        assert_eq!(frame.write_hex(&mut buf[..7]), Err(Error::BufferTooSmall));
+        assert_eq!(Frame::<8>::parse_payload_hex(id, "DEAD0bef"), Ok(frame));
+        assert_eq!(Frame::<8>::parse_payload_hex(id, ""), Ok(Frame::new(id, &[]).unwrap()));
+        assert_eq!(Frame::<8>::parse_payload_hex(id, "dea"), Err(Error::InvalidHex));
+        assert_eq!(Frame::<8>::parse_payload_hex(id, "de0x"), Err(Error::InvalidHex));
+        assert_eq!(Frame::<2>::parse_payload_hex(id, "dead0bef"), Err(Error::WrongLength));
+    }
+
+    #[test]
+    fn check_copy_from() {
+        let mut frame = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[1, 2, 3, 4]).unwrap();
+        let id = FrameId::new_extended(0x123).unwrap();
+        assert_eq!(frame.copy_from(id, &[5, 6]), Ok(()));
+        assert_eq!(frame, Frame::new(id, &[5, 6]).unwrap());
+        assert_eq!(frame.copy_from(id, &[0; 9]), Err(Error::WrongLength));
+        assert_eq!(frame.data(), &[5, 6]);
+    }
+
+    #[test]
+    fn check_from_byte_iter() {
+        let id = FrameId::new_standard(0x123).unwrap();
+        let mut bytes = [1u8, 2, 3, 4].iter().cloned();
+        assert_eq!(Frame::<8>::from_byte_iter(id, &mut bytes, 3), Ok(Frame::new(id, &[1, 2, 3]).unwrap()));
+        assert_eq!(bytes.next(), Some(4));
+        assert_eq!(Frame::<8>::from_byte_iter(id, [1u8, 2].iter().cloned(), 3), Err(Error::UnexpectedEnd));
+        assert_eq!(Frame::<2>::from_byte_iter(id, [1u8, 2, 3].iter().cloned(), 3), Err(Error::WrongLength));
+    }
+
+    #[test]
+    fn check_spare_capacity() {
+        let mut frame = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[1, 2]).unwrap();
+        let spare = frame.spare_capacity_mut();
+        assert_eq!(spare.len(), 6);
+        spare[..3].copy_from_slice(&[3, 4, 5]);
+        assert_eq!(frame.commit(3), Ok(()));
+        assert_eq!(frame.data(), &[1, 2, 3, 4, 5]);
+        assert_eq!(frame.commit(4), Err(Error::WrongLength));
+        assert_eq!(frame.commit(3), Ok(()));
+        assert_eq!(frame.spare_capacity_mut().len(), 0);
+    }
+
+    #[test]
+    fn check_resize() {
+        let id = FrameId::new_standard(0x123).unwrap();
+        let classic = Frame::<8>::new(id, &[1, 2, 3]).unwrap();
+        let fd: Frame<64> = classic.resize().unwrap();
+        assert_eq!(fd.data(), &[1, 2, 3]);
+        assert_eq!(fd.resize::<8>(), Some(classic));
+        let fd = Frame::<64>::new(id, &[0; 9]).unwrap();
+        assert_eq!(fd.resize::<8>(), None);
+    }
+
+    #[test]
+    fn check_data_at() {
+        let frame = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[1, 2, 3, 4, 5]).unwrap();
+        assert!(!frame.is_empty_payload());
+        assert_eq!(frame.data_at::<2>(0), Some([1, 2]));
+        assert_eq!(frame.data_at::<2>(3), Some([4, 5]));
+        assert_eq!(frame.data_at::<2>(4), None);
+        assert_eq!(frame.data_at::<0>(5), Some([]));
+        assert_eq!(frame.data_at::<1>(usize::MAX), None);
+        let empty = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[]).unwrap();
+        assert!(empty.is_empty_payload());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Frame;
+use crate::{Frame, FrameRef, FrameId};
 use core::cmp::Ordering;
 
 pub trait MarkerTraits: Eq + PartialEq + Copy + Clone {}
@@ -20,14 +20,69 @@ impl PartialEq<Self> for NoGrouping {
 }
 impl Eq for NoGrouping {}
 
+/// Sequence number type used to keep push order of frames with equal IDs,
+/// `i32` with `wide-seq` feature for very deep or long lived queues.
+#[cfg(not(feature = "wide-seq"))]
+pub type Seq = i16;
+#[cfg(not(feature = "wide-seq"))]
+type USeq = u16;
+#[cfg(feature = "wide-seq")]
+pub type Seq = i32;
+#[cfg(feature = "wide-seq")]
+type USeq = u32;
+
+/// Per frame timestamp given to `Heap::push_at`, zero sized unless `timestamp` feature is enabled.
+#[cfg(feature = "timestamp")]
+pub(crate) type Timestamp = u32;
+#[cfg(not(feature = "timestamp"))]
+pub(crate) type Timestamp = ();
+#[cfg(feature = "timestamp")]
+pub(crate) const NO_TIMESTAMP: Timestamp = 0;
+#[cfg(not(feature = "timestamp"))]
+pub(crate) const NO_TIMESTAMP: Timestamp = ();
+
+/// Per frame deadline given to `Heap::push_with_deadline`, zero sized unless `deadline` feature is enabled.
+#[cfg(feature = "deadline")]
+pub(crate) type Deadline = Option<u32>;
+#[cfg(not(feature = "deadline"))]
+pub(crate) type Deadline = ();
+#[cfg(feature = "deadline")]
+pub(crate) const NO_DEADLINE: Deadline = None;
+#[cfg(not(feature = "deadline"))]
+pub(crate) const NO_DEADLINE: Deadline = ();
+
 #[derive(Eq, PartialEq, Copy, Clone)]
 enum HeapElement<M: MarkerTraits, G: GroupTraits, const MTU: usize> {
     Hole,
-    Filled(Frame<MTU>, i16, M, G)
+    Filled {
+        frame: Frame<MTU>,
+        /// Ordering key, equal to frame.id unless whole group is scheduled at it's highest priority,
+        /// see [GroupingHeap::push_group_atomic].
+        prio: FrameId,
+        /// Push order of frames with equal IDs, compared as age relative to the heap's counter, so resident frames
+        /// are ordered correctly as long as the oldest one was pushed less than 2^16 (2^32 with wide-seq) pushes ago.
+        /// See [Heap::renumber] for long lived heaps.
+        seq: Seq,
+        marker: M,
+        group: G,
+        /// Caller provided timestamp, not used for ordering
+        ts: Timestamp,
+        /// Time by which the frame must be sent, see `Heap::min_slack`, not used for ordering either
+        deadline: Deadline,
+    }
 }
 
-impl<M: MarkerTraits, G: GroupTraits, const MTU: usize> Ord for HeapElement<M, G, MTU> {
-    fn cmp(&self, other: &Self) -> Ordering {
+impl<M: MarkerTraits, G: GroupTraits, const MTU: usize> HeapElement<M, G, MTU> {
+    fn with_seq(mut self, seq: Seq) -> Self {
+        if let HeapElement::Filled { seq: elem_seq, .. } = &mut self {
+            *elem_seq = seq;
+        }
+        self
+    }
+
+    /// Order by priority and then by age relative to the heap's seq counter now. Unlike comparing seqs pairwise,
+    /// this is total and transitive for any set of resident frames, even if their seqs span more than half the range.
+    fn cmp_ordered(&self, other: &Self, seq_order: SeqOrder, now: Seq) -> Ordering {
         use Ordering::*;
         match self {
             HeapElement::Hole => {
@@ -35,17 +90,27 @@ impl<M: MarkerTraits, G: GroupTraits, const MTU: usize> Ord for HeapElement<M, G
                     // Hole's priority are equal, no need to move them around
                     HeapElement::Hole => { Equal }
                     // Any filled element priority is higher (less in can bus terminology)
-                    HeapElement::Filled(_, _, _, _) => { Greater }
+                    HeapElement::Filled { .. } => { Greater }
                 }
             }
-            HeapElement::Filled(self_frame, self_seq, _, _) => {
+            HeapElement::Filled { frame: self_frame, prio: self_prio, seq: self_seq, .. } => {
                 match other {
                     // Any filled element priority is higher (less in can bus terminology)
                     HeapElement::Hole => { Less }
-                    HeapElement::Filled(other_frame, other_seq, _, _) => {
-                        match self_frame.cmp(other_frame) {
+                    HeapElement::Filled { frame: other_frame, prio: other_prio, seq: other_seq, .. } => {
+                        match self_prio.cmp(other_prio) {
                             Less => { Less }
-                            Equal => { self_seq.wrapping_sub(*other_seq).cmp(&0) }
+                            Equal => {
+                                let self_age = now.wrapping_sub(*self_seq) as USeq;
+                                let other_age = now.wrapping_sub(*other_seq) as USeq;
+                                let by_age = match seq_order {
+                                    SeqOrder::Fifo => other_age.cmp(&self_age),
+                                    SeqOrder::Lifo => self_age.cmp(&other_age)
+                                };
+                                // Equal seqs can't tell the order, fall back to payload so that the order stays total
+                                by_age.then_with(|| self_frame.data().cmp(other_frame.data()))
+                                    .then_with(|| self_frame.id.cmp(&other_frame.id))
+                            }
                             Greater => { Greater }
                         }
                     }
@@ -55,24 +120,77 @@ impl<M: MarkerTraits, G: GroupTraits, const MTU: usize> Ord for HeapElement<M, G
     }
 }
 
-impl<M: MarkerTraits, G: GroupTraits, const MTU: usize> PartialOrd for HeapElement<M, G, MTU> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[derive(Eq, PartialEq)]
 pub enum SortOn {
     Push,
     Pop,
 }
 
+/// What happened to a frame during [Heap::merge], for wrappers that keep track of the heap's contents.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub(crate) enum MergeEvent {
+    Pushed,
+    Evicted,
+    Rejected,
+}
+
+/// Which of the frames with equal IDs is popped first.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum SeqOrder {
+    /// Oldest first
+    Fifo,
+    /// Newest first, so that the latest value of a signal supersedes stale queued ones
+    Lifo,
+}
+
+/// Frames with the same ID have equal priority and are normally popped in push order,
+/// which lets one group drain completely before the others get a chance.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Fairness {
+    /// Pop equal priority frames in push order
+    Strict,
+    /// Rotate between groups among equal priority frames, falls back to Strict with NoGrouping
+    RoundRobin,
+}
+
+/// What [Heap::push] and group pushes of [GroupingHeap] do when the heap is full.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum OnFull {
+    /// Replace the lowest priority frame together with it's group, if the new frame has higher priority
+    EvictLowestPriority,
+    /// Replace the oldest frame regardless of priority, other frames from it's group are left in place.
+    /// Turns the heap into a bounded recent-history buffer.
+    EvictOldest,
+    /// Reject the new frame
+    Reject,
+}
+
 pub struct Heap<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> {
     data: [HeapElement<M, G, MTU>; N],
     len: usize,
     hint_idx: usize,
     sort_on: SortOn,
-    seq: i16,
+    seq: Seq,
+    fairness: Fairness,
+    /// Group and seq of the last popped frame, for Fairness::RoundRobin
+    last_popped: Option<(G, Seq)>,
+    max_group_eviction: usize,
+    stable_sort: bool,
+    on_full: OnFull,
+    seq_order: SeqOrder,
+    rejected: usize,
+}
+
+/// Copy of a heap's contents and counters, configuration is not included.
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub struct HeapSnapshot<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> {
+    data: [HeapElement<M, G, MTU>; N],
+    len: usize,
+    hint_idx: usize,
+    seq: Seq,
+    /// Group and seq of the last popped frame, for Fairness::RoundRobin
+    last_popped: Option<(G, Seq)>,
+    rejected: usize,
 }
 
 impl<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> Heap<M, G, MTU, N> {
@@ -82,97 +200,718 @@ impl<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> Heap<M,
             len: 0,
             hint_idx: 0,
             sort_on,
-            seq: 0
+            seq: 0,
+            fairness: Fairness::Strict,
+            last_popped: None,
+            max_group_eviction: usize::MAX,
+            stable_sort: false,
+            on_full: OnFull::EvictLowestPriority,
+            seq_order: SeqOrder::Fifo,
+            rejected: 0,
+        }
+    }
+
+    /// Size in bytes of this heap, including bookkeeping fields and padding, for RAM budget checks:
+    /// `const _: () = assert!(Heap::<(), u8, 8, 32>::footprint() <= 2048);`
+    pub const fn footprint() -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    pub fn with_fairness(mut self, fairness: Fairness) -> Self {
+        self.fairness = fairness;
+        self
+    }
+
+    /// When full, [push](Heap::push) evicts the lowest priority frame together with it's whole group.
+    /// If that group holds more than max frames, push is rejected instead. Group pushes of [GroupingHeap]
+    /// are rejected if they would evict more than max frames in total. Unlimited by default.
+    pub fn with_max_group_eviction(mut self, max: usize) -> Self {
+        self.max_group_eviction = max;
+        self
+    }
+
+    /// Use stable sort, so that elements comparing equal are never reordered, for deterministic testing.
+    /// Since sort_stable requires allocation, insertion sort is used instead: O(N^2) time on each sort,
+    /// but no extra memory. Unstable sort is the default.
+    pub fn with_stable_sort(mut self, stable: bool) -> Self {
+        self.stable_sort = stable;
+        self
+    }
+
+    pub fn with_on_full(mut self, on_full: OnFull) -> Self {
+        self.on_full = on_full;
+        self
+    }
+
+    pub fn with_seq_order(mut self, seq_order: SeqOrder) -> Self {
+        self.seq_order = seq_order;
+        self
+    }
+
+    fn sort(&mut self) {
+        let (seq_order, now) = (self.seq_order, self.seq);
+        if self.stable_sort {
+            insertion_sort_by(&mut self.data, |a, b| a.cmp_ordered(b, seq_order, now));
+        } else {
+            self.data.sort_unstable_by(|a, b| a.cmp_ordered(b, seq_order, now));
         }
+        self.hint_idx = 0;
+        debug_assert!(self.is_sorted());
+    }
+
+    /// Whether storage is in pop order, which is always the case right after sorting.
+    /// With SortOn::Pop it is usually not sorted in between pops.
+    pub fn is_sorted(&self) -> bool {
+        let (seq_order, now) = (self.seq_order, self.seq);
+        self.data.windows(2).all(|pair| pair[0].cmp_ordered(&pair[1], seq_order, now) != Ordering::Greater)
     }
 
     pub fn push(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<usize, Frame<MTU>> {
+        self.push_at_with(frame, marker, group, NO_TIMESTAMP, NO_DEADLINE, |_, _| {})
+    }
+
+    /// Same as [push](Heap::push), but also stores a caller provided timestamp alongside the frame.
+    /// Timestamp does not affect ordering and is returned back by [pop_with_timestamp](Heap::pop_with_timestamp),
+    /// for example to compute how long a frame spent in the queue. Requires `timestamp` feature, which adds
+    /// 4 bytes to every slot.
+    #[cfg(feature = "timestamp")]
+    pub fn push_at(&mut self, frame: Frame<MTU>, marker: M, group: G, ts: u32) -> Result<usize, Frame<MTU>> {
+        self.push_at_with(frame, marker, group, ts, NO_DEADLINE, |_, _| {})
+    }
+
+    /// Same as [push](Heap::push), but also stores the time by which the frame must be sent,
+    /// see [min_slack](Heap::min_slack). Deadline does not affect ordering. Requires `deadline` feature,
+    /// which adds 8 bytes to every slot.
+    #[cfg(feature = "deadline")]
+    pub fn push_with_deadline(&mut self, frame: Frame<MTU>, marker: M, group: G, deadline: u32) -> Result<usize, Frame<MTU>> {
+        self.push_at_with(frame, marker, group, NO_TIMESTAMP, Some(deadline), |_, _| {})
+    }
+
+    /// Same as [push_at](Heap::push_at), with an optional deadline and calling on_evict with the ID and seq
+    /// of every evicted frame.
+    pub(crate) fn push_at_with(
+        &mut self,
+        frame: Frame<MTU>,
+        marker: M,
+        group: G,
+        ts: Timestamp,
+        deadline: Deadline,
+        on_evict: impl FnMut(FrameId, Seq)
+    ) -> Result<usize, Frame<MTU>> {
+        let elem = HeapElement::Filled { frame, prio: frame.id, seq: 0, marker, group, ts, deadline };
+        self.push_element(elem, on_evict)
+    }
+
+    /// Push an element as is, except for it's seq, which is assigned by the heap.
+    fn push_element(
+        &mut self,
+        elem: HeapElement<M, G, MTU>,
+        mut on_evict: impl FnMut(FrameId, Seq)
+    ) -> Result<usize, Frame<MTU>> {
+        let (frame, prio) = match elem {
+            HeapElement::Filled { frame, prio, .. } => (frame, prio),
+            HeapElement::Hole => unreachable!()
+        };
         let mut replaced = 0;
-        if self.len == N {
+        if self.len == N && self.on_full == OnFull::Reject {
+            return Err(frame);
+        } else if self.len == N && self.on_full == OnFull::EvictOldest {
+            let now = self.seq;
+            let oldest = self.data.iter_mut().max_by_key(|elem| match elem {
+                HeapElement::Filled { seq, .. } => now.wrapping_sub(*seq) as USeq,
+                HeapElement::Hole => 0
+            });
+            if let Some(oldest) = oldest {
+                if let HeapElement::Filled { frame, seq, .. } = oldest {
+                    on_evict(frame.id, *seq);
+                }
+                *oldest = elem.with_seq(self.seq);
+            }
+            self.seq = self.seq.wrapping_add(1);
+            replaced = 1;
+        } else if self.len == N {
             // if self.sort_on == SortOn::Push {
-                self.data.sort_unstable();
-                self.hint_idx = 0;
+                self.sort();
             // }
-            match self.data[N - 1] {
-                HeapElement::Filled(stored_frame, _, _, _) => {
-                    if frame < stored_frame {
-                        let old_group = match self.data[N - 1] {
-                            HeapElement::Hole => { unreachable!() }
-                            HeapElement::Filled(_, _, _, og) => og
-                        };
-                        self.data[N - 1] = HeapElement::Filled(frame, self.seq, marker, group);
-                        replaced = 1;
-
-                        // Remove all frames from the same group as well
-                        for elem in self.data.iter_mut() {
-                            match elem {
-                                HeapElement::Hole => {}
-                                HeapElement::Filled(_, _, _, group) => {
-                                    if old_group == *group {
-                                        *elem = HeapElement::Hole;
-                                        replaced += 1;
-                                    }
-                                }
-                            }
-                        }
+            let old_group = match self.data[N - 1] {
+                HeapElement::Filled { prio: lowest_prio, group, .. } => {
+                    if prio < lowest_prio {
+                        group
                     } else {
                         return Err(frame);
                     }
                 }
                 HeapElement::Hole => unreachable!()
+            };
+            let same_group = |elem: &HeapElement<M, G, MTU>| match elem {
+                HeapElement::Filled { group, .. } => *group == old_group,
+                HeapElement::Hole => false
+            };
+            let group_len = self.data[..N - 1].iter().filter(|elem| same_group(elem)).count() + 1;
+            if group_len > self.max_group_eviction {
+                return Err(frame);
             }
+            if let HeapElement::Filled { frame, seq, .. } = self.data[N - 1] {
+                on_evict(frame.id, seq);
+            }
+            self.data[N - 1] = elem.with_seq(self.seq);
+            replaced = group_len;
+
+            // Remove all frames from the same group as well
+            for elem in self.data[..N - 1].iter_mut() {
+                if same_group(elem) {
+                    if let HeapElement::Filled { frame, seq, .. } = elem {
+                        on_evict(frame.id, *seq);
+                    }
+                    *elem = HeapElement::Hole;
+                }
+            }
+            self.len -= group_len - 1;
+            self.seq = self.seq.wrapping_add(1);
         } else {
+            self.insert_element(elem);
+        }
+        if self.sort_on == SortOn::Push {
+            self.sort();
+        }
+
+        Ok(replaced)
+    }
+
+    /// Put a frame into the first hole, there must be one.
+    fn insert(&mut self, frame: Frame<MTU>, prio: FrameId, marker: M, group: G, ts: Timestamp, deadline: Deadline) {
+        self.insert_element(HeapElement::Filled { frame, prio, seq: 0, marker, group, ts, deadline });
+    }
+
+    /// Same as [insert](Heap::insert) for an already built element, it's seq is assigned by the heap.
+    fn insert_element(&mut self, elem: HeapElement<M, G, MTU>) {
+        for slot in self.data.iter_mut() {
+            if *slot == HeapElement::Hole {
+                *slot = elem.with_seq(self.seq);
+                break;
+            }
+        }
+        self.len += 1;
+        self.seq = self.seq.wrapping_add(1);
+    }
+
+    /// Make sure there are at least count holes, following [OnFull] rules: by evicting whole groups of lower
+    /// priority than prio, or the oldest frames. Nothing is evicted if that's not possible.
+    /// Returns the number of evicted frames.
+    fn make_room(&mut self, count: usize, prio: FrameId) -> Result<usize, ()> {
+        if count > N {
+            return Err(());
+        }
+        if N - self.len >= count {
+            return Ok(0);
+        }
+        match self.on_full {
+            OnFull::Reject => return Err(()),
+            OnFull::EvictOldest => {
+                let evicted = count - (N - self.len);
+                for _ in 0..evicted {
+                    let now = self.seq;
+                    let oldest = self.data.iter_mut().max_by_key(|elem| match elem {
+                        HeapElement::Filled { seq, .. } => Some(now.wrapping_sub(*seq) as USeq),
+                        HeapElement::Hole => None
+                    });
+                    if let Some(oldest) = oldest {
+                        *oldest = HeapElement::Hole;
+                    }
+                }
+                self.len -= evicted;
+                self.hint_idx = 0;
+                return Ok(evicted);
+            }
+            OnFull::EvictLowestPriority => {}
+        }
+        self.sort();
+        let group_of = |elem: &HeapElement<M, G, MTU>| match elem {
+            HeapElement::Filled { group, .. } => *group,
+            HeapElement::Hole => unreachable!()
+        };
+        // Walk from the lowest priority end, counting frames of the groups that would be evicted
+        let mut freed = N - self.len;
+        let mut first_evicted = self.len;
+        while freed < count {
+            first_evicted -= 1;
+            let group = match self.data[first_evicted] {
+                HeapElement::Filled { prio: lower_prio, group, .. } => {
+                    if prio >= lower_prio {
+                        return Err(());
+                    }
+                    group
+                }
+                HeapElement::Hole => unreachable!()
+            };
+            let already_counted = self.data[first_evicted + 1..self.len].iter()
+                .any(|elem| group_of(elem) == group);
+            if !already_counted {
+                freed += self.data[..self.len].iter().filter(|elem| group_of(elem) == group).count();
+            }
+        }
+        if freed - (N - self.len) > self.max_group_eviction {
+            return Err(());
+        }
+        let mut evicted = 0;
+        for i in first_evicted..self.len {
+            let group = match self.data[i] {
+                HeapElement::Filled { group, .. } => group,
+                HeapElement::Hole => continue
+            };
             for elem in self.data.iter_mut() {
-                if *elem == HeapElement::Hole {
-                    *elem = HeapElement::Filled(frame, self.seq, marker, group);
-                    break;
+                if let HeapElement::Filled { group: other_group, .. } = elem {
+                    if *other_group == group {
+                        *elem = HeapElement::Hole;
+                        evicted += 1;
+                    }
                 }
             }
-            self.len += 1;
         }
+        self.len -= evicted;
+        Ok(evicted)
+    }
+
+    /// Put a frame into a free slot, never evicting anything. If the heap is full, frame is dropped and counted,
+    /// see [rejected_count](Heap::rejected_count), and false is returned.
+    pub fn push_saturating(&mut self, frame: Frame<MTU>, marker: M, group: G) -> bool {
+        if self.len == N {
+            self.rejected = self.rejected.wrapping_add(1);
+            return false;
+        }
+        self.insert(frame, frame.id, marker, group, NO_TIMESTAMP, NO_DEADLINE);
         if self.sort_on == SortOn::Push {
-            self.data.sort_unstable();
-            self.hint_idx = 0;
+            self.sort();
+        }
+        true
+    }
+
+    /// Number of frames dropped by [push_saturating](Heap::push_saturating), wraps around on overflow.
+    pub fn rejected_count(&self) -> usize {
+        self.rejected
+    }
+
+    /// Same as [push](Heap::push), but skips the frame if an equal one (same ID and payload) is already queued,
+    /// returning Ok(None) in that case.
+    pub fn push_dedup(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<Option<usize>, Frame<MTU>> {
+        if self.contains(&frame.as_frame_ref()) {
+            return Ok(None);
+        }
+        self.push(frame, marker, group).map(Some)
+    }
+
+    /// Same as [push](Heap::push), but if a frame with the same ID is already queued, it's payload and marker
+    /// are overwritten instead ("latest value wins"), returning Ok(None) in that case. Overwritten frame keeps
+    /// it's priority and group, but gets a new seq, as if it was just pushed.
+    pub fn push_coalesce(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<Option<usize>, Frame<MTU>> {
+        let existing = self.data.iter_mut().find(|elem| match elem {
+            HeapElement::Filled { frame: queued, .. } => queued.id == frame.id,
+            HeapElement::Hole => false
+        });
+        match existing {
+            Some(HeapElement::Filled { frame: queued, marker: queued_marker, seq, ts, deadline, .. }) => {
+                *queued = frame;
+                *queued_marker = marker;
+                *seq = self.seq;
+                *ts = NO_TIMESTAMP;
+                *deadline = NO_DEADLINE;
+            }
+            _ => return self.push(frame, marker, group).map(Some)
         }
         self.seq = self.seq.wrapping_add(1);
+        if self.sort_on == SortOn::Push {
+            self.sort();
+        }
+        Ok(None)
+    }
 
-        Ok(replaced)
+    /// Whether a frame with the same ID and payload is queued, regardless of it's marker and group.
+    pub fn contains(&self, frame: &FrameRef) -> bool {
+        self.data.iter().any(|elem| match elem {
+            HeapElement::Filled { frame: queued, .. } => queued.as_frame_ref() == *frame,
+            HeapElement::Hole => false
+        })
     }
 
     pub fn pop(&mut self) -> Option<(Frame<MTU>, M)> {
+        let idx = self.next_idx()?;
+        let (frame, marker, _) = self.take(idx);
+        Some((frame, marker))
+    }
+
+    /// Same as [pop](Heap::pop), but also returns the timestamp given to [push_at](Heap::push_at)
+    /// (or 0 if frame was pushed without one).
+    #[cfg(feature = "timestamp")]
+    pub fn pop_with_timestamp(&mut self) -> Option<(Frame<MTU>, M, u32)> {
+        let idx = self.next_idx()?;
+        Some(self.take(idx))
+    }
+
+    /// Same as [pop_with_timestamp](Heap::pop_with_timestamp), but also returns seq the frame was pushed with.
+    pub(crate) fn pop_with_seq(&mut self) -> Option<(Frame<MTU>, M, Timestamp, Seq)> {
+        let idx = self.next_idx()?;
+        let seq = self.seq_at(idx);
+        let (frame, marker, ts) = self.take(idx);
+        Some((frame, marker, ts, seq))
+    }
+
+    /// Marker of the frame that would be popped next (with Fairness::Strict), without copying the frame.
+    /// Works with an immutable reference, so instead of sorting, highest priority frame is found with a linear scan.
+    pub fn peek_marker(&self) -> Option<&M> {
+        let (seq_order, now) = (self.seq_order, self.seq);
+        match self.data.iter().min_by(|a, b| a.cmp_ordered(b, seq_order, now))? {
+            HeapElement::Filled { marker, .. } => Some(marker),
+            HeapElement::Hole => None
+        }
+    }
+
+    /// Pop the highest priority frame only if pred returns true for it, sorting at most once.
+    pub fn pop_if(&mut self, pred: impl FnOnce(&FrameRef, &M) -> bool) -> Option<(Frame<MTU>, M)> {
+        self.pop_if_with_seq(pred).map(|(frame, marker, _)| (frame, marker))
+    }
+
+    /// Same as [pop_if](Heap::pop_if), but also returns seq the frame was pushed with.
+    pub(crate) fn pop_if_with_seq(&mut self, pred: impl FnOnce(&FrameRef, &M) -> bool) -> Option<(Frame<MTU>, M, Seq)> {
+        let idx = self.next_idx()?;
+        match &self.data[idx] {
+            HeapElement::Filled { frame, marker, .. } => {
+                if !pred(&frame.as_frame_ref(), marker) {
+                    return None;
+                }
+            }
+            HeapElement::Hole => unreachable!()
+        }
+        let seq = self.seq_at(idx);
+        let (frame, marker, _) = self.take(idx);
+        Some((frame, marker, seq))
+    }
+
+    /// Pop the frame with the earliest timestamp, that is not later than now, ties are broken by priority.
+    /// Timestamps are compared with wrapping arithmetic and must be within 2^31 of now.
+    #[cfg(feature = "timestamp")]
+    pub(crate) fn pop_due(&mut self, now: u32) -> Option<(Frame<MTU>, M, u32)> {
+        let seq_order = self.seq_order;
+        let mut due: Option<(usize, u32)> = None;
+        for (i, elem) in self.data.iter().enumerate() {
+            let ts = match elem {
+                HeapElement::Filled { ts, .. } => *ts,
+                HeapElement::Hole => continue
+            };
+            let overdue = now.wrapping_sub(ts);
+            if (overdue as i32) < 0 {
+                continue;
+            }
+            let is_earlier = match due {
+                None => true,
+                Some((due_idx, due_overdue)) => {
+                    overdue > due_overdue ||
+                        (overdue == due_overdue && elem.cmp_ordered(&self.data[due_idx], seq_order, self.seq) == Ordering::Less)
+                }
+            };
+            if is_earlier {
+                due = Some((i, overdue));
+            }
+        }
+        let (idx, _) = due?;
+        Some(self.take(idx))
+    }
+
+    /// Pop up to out.len() frames in priority order, returning how many were written.
+    /// With SortOn::Pop sorting is done only once, which is faster than calling [pop](Heap::pop) in a loop.
+    pub fn pop_into(&mut self, out: &mut [(Frame<MTU>, M)]) -> usize {
+        self.pop_into_with(out, |_, _| {})
+    }
+
+    /// Same as [pop_into](Heap::pop_into), calling on_pop with the ID and seq of every popped frame.
+    pub(crate) fn pop_into_with(&mut self, out: &mut [(Frame<MTU>, M)], mut on_pop: impl FnMut(FrameId, Seq)) -> usize {
+        if self.len == 0 {
+            return 0;
+        }
+        if self.sort_on == SortOn::Pop {
+            self.sort();
+        }
+        let mut count = 0;
+        for slot in out.iter_mut() {
+            let idx = match self.next_idx_sorted() {
+                Some(idx) => idx,
+                None => break
+            };
+            let seq = self.seq_at(idx);
+            let (frame, marker, _) = self.take(idx);
+            on_pop(frame.id, seq);
+            *slot = (frame, marker);
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns the index of the highest priority element, sorting beforehand if needed.
+    fn next_idx(&mut self) -> Option<usize> {
         if self.len == 0 {
             return None;
         }
         if self.sort_on == SortOn::Pop {
-            self.data.sort_unstable();
-            self.hint_idx = 0;
+            self.sort();
+        }
+        self.next_idx_sorted()
+    }
+
+    /// Same as [next_idx](Heap::next_idx), assuming that data is already sorted.
+    fn next_idx_sorted(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
         }
         if self.hint_idx >= N {
             self.hint_idx = 0;
         }
-        match self.data[self.hint_idx] {
-            HeapElement::Filled(frame, _, marker, _) => {
-                self.data[self.hint_idx] = HeapElement::Hole;
-                self.hint_idx += 1;
-                self.len -= 1;
-                return Some((frame, marker));
-            },
-            HeapElement::Hole => {
-                for item in self.data.iter_mut() {
+        if self.data[self.hint_idx] == HeapElement::Hole {
+            self.hint_idx = self.data.iter().position(|elem| *elem != HeapElement::Hole)?;
+        }
+        if self.fairness == Fairness::RoundRobin {
+            return Some(self.next_idx_round_robin());
+        }
+        Some(self.hint_idx)
+    }
+
+    /// Among the frames with the same ID as the one at hint_idx, rotate between groups: pick the first frame
+    /// of a group other than the last popped one, that was pushed next after the last popped frame (next before it
+    /// with SeqOrder::Lifo), wrapping around. hint_idx is not advanced past the skipped frames, so they are popped later.
+    fn next_idx_round_robin(&self) -> usize {
+        // Zero sized group type, such as NoGrouping, can't tell groups apart
+        if core::mem::size_of::<G>() == 0 {
+            return self.hint_idx;
+        }
+        let top_id = match self.data[self.hint_idx] {
+            HeapElement::Filled { prio, .. } => prio,
+            HeapElement::Hole => unreachable!()
+        };
+        let (last_group, last_seq) = match self.last_popped {
+            Some(last_popped) => last_popped,
+            None => return self.hint_idx
+        };
+        let in_group = |elem: &HeapElement<M, G, MTU>, group: G| match elem {
+            HeapElement::Filled { group: elem_group, .. } => *elem_group == group,
+            HeapElement::Hole => false
+        };
+        let mut next: Option<(usize, USeq)> = None;
+        for (i, elem) in self.data.iter().enumerate().skip(self.hint_idx) {
+            let (group, seq) = match elem {
+                HeapElement::Hole => continue,
+                HeapElement::Filled { prio, group, seq, .. } => {
+                    if *prio != top_id {
+                        break;
+                    }
+                    (*group, *seq)
+                }
+            };
+            if group == last_group || self.data[self.hint_idx..i].iter().any(|elem| in_group(elem, group)) {
+                continue;
+            }
+            let distance = match self.seq_order {
+                SeqOrder::Fifo => seq.wrapping_sub(last_seq) as USeq,
+                SeqOrder::Lifo => last_seq.wrapping_sub(seq) as USeq
+            };
+            match next {
+                Some((_, best)) if best <= distance => {}
+                _ => next = Some((i, distance))
+            }
+        }
+        next.map_or(self.hint_idx, |(i, _)| i)
+    }
+
+    fn seq_at(&self, idx: usize) -> Seq {
+        match self.data[idx] {
+            HeapElement::Filled { seq, .. } => seq,
+            HeapElement::Hole => unreachable!()
+        }
+    }
+
+    /// Turns element at idx into a Hole and returns it's contents.
+    fn take(&mut self, idx: usize) -> (Frame<MTU>, M, Timestamp) {
+        match core::mem::replace(&mut self.data[idx], HeapElement::Hole) {
+            HeapElement::Filled { frame, marker, group, seq, ts, .. } => {
+                if idx == self.hint_idx {
                     self.hint_idx += 1;
-                    match item {
-                        HeapElement::Filled(frame, _, marker, _) => {
-                            let popped = (frame.clone(), marker.clone());
-                            *item = HeapElement::Hole;
-                            self.len -= 1;
-                            return Some(popped);
+                }
+                self.len -= 1;
+                self.last_popped = Some((group, seq));
+                (frame, marker, ts)
+            }
+            HeapElement::Hole => unreachable!()
+        }
+    }
+
+    /// Iterate over queued frames in the order they would be popped (with Fairness::Strict), without
+    /// mutating the heap. Since there is no allocation, indices are sorted in a caller provided scratch buffer.
+    pub fn iter_ordered<'a>(&'a self, scratch: &'a mut [usize; N]) -> impl Iterator<Item = (FrameRef<'a>, M)> + 'a {
+        let mut len = 0;
+        for (i, elem) in self.data.iter().enumerate() {
+            if let HeapElement::Filled { .. } = elem {
+                scratch[len] = i;
+                len += 1;
+            }
+        }
+        let indices = &mut scratch[..len];
+        if self.stable_sort {
+            insertion_sort_by(indices, |a, b| self.data[*a].cmp_ordered(&self.data[*b], self.seq_order, self.seq));
+        } else {
+            indices.sort_unstable_by(|a, b| self.data[*a].cmp_ordered(&self.data[*b], self.seq_order, self.seq));
+        }
+        indices.iter().map(move |i| match &self.data[*i] {
+            HeapElement::Filled { frame, marker, .. } => (frame.as_frame_ref(), *marker),
+            HeapElement::Hole => unreachable!()
+        })
+    }
+
+    /// Removes all frames for which pred returns true and gives them to out.
+    pub fn drain_filter(
+        &mut self,
+        pred: impl FnMut(&FrameRef, &M) -> bool,
+        out: &mut impl FnMut(Frame<MTU>, M)
+    ) {
+        self.drain_filter_with(pred, &mut |frame, marker, _| out(frame, marker));
+    }
+
+    /// Same as [drain_filter](Heap::drain_filter), also giving out the seq of removed frames.
+    pub(crate) fn drain_filter_with(
+        &mut self,
+        mut pred: impl FnMut(&FrameRef, &M) -> bool,
+        out: &mut impl FnMut(Frame<MTU>, M, Seq)
+    ) {
+        for elem in self.data.iter_mut() {
+            if let HeapElement::Filled { frame, marker, seq, .. } = *elem {
+                if pred(&frame.as_frame_ref(), &marker) {
+                    *elem = HeapElement::Hole;
+                    self.len -= 1;
+                    out(frame, marker, seq);
+                }
+            }
+        }
+    }
+
+    /// Let f modify markers of all queued frames in place, ordering is not affected.
+    pub fn update_markers(&mut self, mut f: impl FnMut(&FrameRef, &mut M)) {
+        for elem in self.data.iter_mut() {
+            if let HeapElement::Filled { frame, marker, .. } = elem {
+                f(&frame.as_frame_ref(), marker);
+            }
+        }
+    }
+
+    /// Removes all frames with markers matching pred, returning how many were removed.
+    pub fn clear_matching(&mut self, pred: impl Fn(&M) -> bool) -> usize {
+        let mut removed = 0;
+        self.drain_filter(|_, marker| pred(marker), &mut |_, _| removed += 1);
+        removed
+    }
+
+    /// Moves all frames from other into self in priority order, applying the same eviction rules as
+    /// [push](Heap::push) when self is full, and leaves other empty.
+    /// Frames with equal IDs keep their relative order. Returns how many frames were dropped.
+    pub fn merge<const N2: usize>(&mut self, other: &mut Heap<M, G, MTU, N2>) -> usize {
+        self.merge_with(other, |_, _, _| {})
+    }
+
+    /// Same as [merge](Heap::merge), calling on_event with the ID and seq of every pushed, evicted and rejected frame.
+    pub(crate) fn merge_with<const N2: usize>(
+        &mut self,
+        other: &mut Heap<M, G, MTU, N2>,
+        mut on_event: impl FnMut(MergeEvent, FrameId, Seq)
+    ) -> usize {
+        // Oldest first among equal IDs regardless of other's SeqOrder, so that push order is kept
+        let now = other.seq;
+        other.data.sort_unstable_by(|a, b| a.cmp_ordered(b, SeqOrder::Fifo, now));
+        let mut dropped = 0;
+        for elem in other.data.iter() {
+            match *elem {
+                HeapElement::Filled { frame, .. } => {
+                    let seq = self.seq;
+                    // push as is, so that groups pushed with a shared priority stay together
+                    match self.push_element(*elem, |id, seq| on_event(MergeEvent::Evicted, id, seq)) {
+                        Ok(replaced) => {
+                            on_event(MergeEvent::Pushed, frame.id, seq);
+                            dropped += replaced;
+                        }
+                        Err(_) => {
+                            on_event(MergeEvent::Rejected, frame.id, seq);
+                            dropped += 1;
                         }
-                        HeapElement::Hole => {}
                     }
                 }
+                HeapElement::Hole => break
+            }
+        }
+        other.clear();
+        dropped
+    }
+
+    pub fn snapshot(&self) -> HeapSnapshot<M, G, MTU, N> {
+        HeapSnapshot {
+            data: self.data,
+            len: self.len,
+            hint_idx: self.hint_idx,
+            seq: self.seq,
+            last_popped: self.last_popped,
+            rejected: self.rejected,
+        }
+    }
+
+    pub fn restore(&mut self, snap: &HeapSnapshot<M, G, MTU, N>) {
+        self.data = snap.data;
+        self.len = snap.len;
+        self.hint_idx = snap.hint_idx;
+        self.seq = snap.seq;
+        self.last_popped = snap.last_popped;
+        self.rejected = snap.rejected;
+    }
+
+    /// Move all frames to the front, keeping their relative order, so that pop doesn't have to skip over holes.
+    pub fn compact(&mut self) {
+        let mut write_idx = 0;
+        for read_idx in 0..N {
+            if self.data[read_idx] != HeapElement::Hole {
+                self.data.swap(write_idx, read_idx);
+                write_idx += 1;
+            }
+        }
+        self.hint_idx = 0;
+    }
+
+    /// Reassigns resident seq values to 0..len in push order, so that the next pushes can't misorder
+    /// frames already in the queue. Call it periodically on a long lived heap, that may accumulate close to
+    /// 2^16 pushes (2^32 with wide-seq) while some frames still wait to be popped.
+    pub fn renumber(&mut self) {
+        let now = self.seq;
+        // Oldest first, holes last
+        self.data.sort_unstable_by_key(|elem| match elem {
+            HeapElement::Filled { seq, .. } => now.wrapping_sub(*seq) as USeq,
+            HeapElement::Hole => 0
+        });
+        self.data.reverse();
+        let mut seq: Seq = 0;
+        for elem in self.data.iter_mut() {
+            if let HeapElement::Filled { seq: elem_seq, .. } = elem {
+                *elem_seq = seq;
+                seq = seq.wrapping_add(1);
             }
         }
-        None
+        self.seq = seq;
+        self.sort();
+    }
+
+    /// Smallest deadline - now among queued frames that have a deadline, negative if any of them is overdue.
+    /// Time is allowed to wrap, but deadlines must be within 2^31 of now.
+    #[cfg(feature = "deadline")]
+    pub fn min_slack(&self, now: u32) -> Option<i32> {
+        self.data.iter().filter_map(|elem| match elem {
+            HeapElement::Filled { deadline: Some(deadline), .. } => Some(deadline.wrapping_sub(now) as i32),
+            _ => None
+        }).min()
+    }
+
+    /// Seq that will be given to the next pushed frame, for debugging ordering of equal IDs.
+    pub fn current_seq(&self) -> Seq {
+        self.seq
     }
 
     pub fn clear(&mut self) {
@@ -185,6 +924,37 @@ impl<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> Heap<M,
     pub fn len(&self) -> usize {
         self.len
     }
+
+    /// Queued frames in storage order, which is not necessarily the priority order.
+    pub fn frames(&self) -> impl Iterator<Item = &Frame<MTU>> {
+        self.data.iter().filter_map(|elem| match elem {
+            HeapElement::Filled { frame, .. } => Some(frame),
+            HeapElement::Hole => None
+        })
+    }
+
+    /// Queued frames with their markers and seq in storage order, which is not necessarily the priority order.
+    pub fn iter_with_seq(&self) -> impl Iterator<Item = (FrameRef<'_>, M, Seq)> {
+        self.data.iter().filter_map(|elem| match elem {
+            HeapElement::Filled { frame, marker, seq, .. } => Some((frame.as_frame_ref(), *marker, *seq)),
+            HeapElement::Hole => None
+        })
+    }
+
+    pub fn free_slots(&self) -> usize {
+        N - self.len
+    }
+}
+
+/// Stable and allocation free, fine for the small arrays heap is used with.
+fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], mut compare: F) {
+    for i in 1..data.len() {
+        let mut j = i;
+        while j > 0 && compare(&data[j - 1], &data[j]) == Ordering::Greater {
+            data.swap(j - 1, j);
+            j -= 1;
+        }
+    }
 }
 
 pub struct PlainHeap<M: MarkerTraits, const MTU: usize, const N: usize> {
@@ -197,116 +967,360 @@ impl<M: MarkerTraits, const MTU: usize, const N: usize> PlainHeap<M, MTU, N> {
         }
     }
 
-    pub fn push(&mut self, frame: Frame<MTU>, marker: M) -> Result<usize, Frame<MTU>> {
-        self.heap.push(frame, marker, NoGrouping{})
+    pub const fn footprint() -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    pub fn with_fairness(mut self, fairness: Fairness) -> Self {
+        self.heap = self.heap.with_fairness(fairness);
+        self
+    }
+
+    pub fn with_stable_sort(mut self, stable: bool) -> Self {
+        self.heap = self.heap.with_stable_sort(stable);
+        self
+    }
+
+    pub fn with_on_full(mut self, on_full: OnFull) -> Self {
+        self.heap = self.heap.with_on_full(on_full);
+        self
+    }
+
+    pub fn with_seq_order(mut self, seq_order: SeqOrder) -> Self {
+        self.heap = self.heap.with_seq_order(seq_order);
+        self
+    }
+
+    pub fn push(&mut self, frame: Frame<MTU>, marker: M) -> Result<usize, Frame<MTU>> {
+        self.heap.push(frame, marker, NoGrouping{})
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn push_at(&mut self, frame: Frame<MTU>, marker: M, ts: u32) -> Result<usize, Frame<MTU>> {
+        self.heap.push_at(frame, marker, NoGrouping{}, ts)
+    }
+
+    pub fn push_dedup(&mut self, frame: Frame<MTU>, marker: M) -> Result<Option<usize>, Frame<MTU>> {
+        self.heap.push_dedup(frame, marker, NoGrouping{})
+    }
+
+    pub fn push_coalesce(&mut self, frame: Frame<MTU>, marker: M) -> Result<Option<usize>, Frame<MTU>> {
+        self.heap.push_coalesce(frame, marker, NoGrouping{})
+    }
+
+    pub fn contains(&self, frame: &FrameRef) -> bool {
+        self.heap.contains(frame)
+    }
+
+    #[cfg(feature = "deadline")]
+    pub fn push_with_deadline(&mut self, frame: Frame<MTU>, marker: M, deadline: u32) -> Result<usize, Frame<MTU>> {
+        self.heap.push_with_deadline(frame, marker, NoGrouping{}, deadline)
+    }
+
+    #[cfg(feature = "deadline")]
+    pub fn min_slack(&self, now: u32) -> Option<i32> {
+        self.heap.min_slack(now)
+    }
+
+    pub fn push_saturating(&mut self, frame: Frame<MTU>, marker: M) -> bool {
+        self.heap.push_saturating(frame, marker, NoGrouping{})
+    }
+
+    pub fn rejected_count(&self) -> usize {
+        self.heap.rejected_count()
+    }
+
+    pub fn pop(&mut self) -> Option<(Frame<MTU>, M)> {
+        self.heap.pop()
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn pop_with_timestamp(&mut self) -> Option<(Frame<MTU>, M, u32)> {
+        self.heap.pop_with_timestamp()
+    }
+
+    pub fn peek_marker(&self) -> Option<&M> {
+        self.heap.peek_marker()
+    }
+
+    pub fn pop_if(&mut self, pred: impl FnOnce(&FrameRef, &M) -> bool) -> Option<(Frame<MTU>, M)> {
+        self.heap.pop_if(pred)
+    }
+
+    pub fn pop_into(&mut self, out: &mut [(Frame<MTU>, M)]) -> usize {
+        self.heap.pop_into(out)
+    }
+
+    pub fn merge<const N2: usize>(&mut self, other: &mut PlainHeap<M, MTU, N2>) -> usize {
+        self.heap.merge(&mut other.heap)
+    }
+
+    pub fn iter_ordered<'a>(&'a self, scratch: &'a mut [usize; N]) -> impl Iterator<Item = (FrameRef<'a>, M)> + 'a {
+        self.heap.iter_ordered(scratch)
+    }
+
+    pub fn drain_filter(
+        &mut self,
+        pred: impl FnMut(&FrameRef, &M) -> bool,
+        out: &mut impl FnMut(Frame<MTU>, M)
+    ) {
+        self.heap.drain_filter(pred, out);
+    }
+
+    pub fn compact(&mut self) {
+        self.heap.compact();
+    }
+
+    pub fn update_markers(&mut self, f: impl FnMut(&FrameRef, &mut M)) {
+        self.heap.update_markers(f)
+    }
+
+    pub fn clear_matching(&mut self, pred: impl Fn(&M) -> bool) -> usize {
+        self.heap.clear_matching(pred)
+    }
+
+    pub fn current_seq(&self) -> Seq {
+        self.heap.current_seq()
+    }
+
+    pub fn iter_with_seq(&self) -> impl Iterator<Item = (FrameRef<'_>, M, Seq)> {
+        self.heap.iter_with_seq()
+    }
+
+    pub fn renumber(&mut self) {
+        self.heap.renumber();
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+/// Heap that assigns a new group key to every push.
+///
+/// Keys are u32 and wrap around after 2^32 pushes, two groups can only alias if one of them is still
+/// queued after that many pushes, which is not a concern in practice.
+pub struct GroupingHeap<M: MarkerTraits, const MTU: usize, const N: usize> {
+    heap: Heap<M, u32, MTU, N>,
+    group_seq: u32,
+}
+impl<M: MarkerTraits, const MTU: usize, const N: usize> GroupingHeap<M, MTU, N> {
+    pub fn new(sort_on: SortOn) -> Self {
+        GroupingHeap {
+            heap: Heap::new(sort_on),
+            group_seq: 0,
+        }
+    }
+
+    pub const fn footprint() -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    pub fn with_fairness(mut self, fairness: Fairness) -> Self {
+        self.heap = self.heap.with_fairness(fairness);
+        self
+    }
+
+    pub fn with_stable_sort(mut self, stable: bool) -> Self {
+        self.heap = self.heap.with_stable_sort(stable);
+        self
+    }
+
+    pub fn with_on_full(mut self, on_full: OnFull) -> Self {
+        self.heap = self.heap.with_on_full(on_full);
+        self
+    }
+
+    pub fn with_seq_order(mut self, seq_order: SeqOrder) -> Self {
+        self.heap = self.heap.with_seq_order(seq_order);
+        self
+    }
+
+    pub fn with_max_group_eviction(mut self, max: usize) -> Self {
+        self.heap = self.heap.with_max_group_eviction(max);
+        self
+    }
+
+    pub fn push(&mut self, frame: Frame<MTU>, marker: M) -> Result<usize, Frame<MTU>> {
+        self.group_seq = self.group_seq.wrapping_add(1);
+        self.heap.push(frame, marker, self.group_seq)
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn push_at(&mut self, frame: Frame<MTU>, marker: M, ts: u32) -> Result<usize, Frame<MTU>> {
+        self.group_seq = self.group_seq.wrapping_add(1);
+        self.heap.push_at(frame, marker, self.group_seq, ts)
+    }
+
+    pub fn push_dedup(&mut self, frame: Frame<MTU>, marker: M) -> Result<Option<usize>, Frame<MTU>> {
+        if self.heap.contains(&frame.as_frame_ref()) {
+            return Ok(None);
+        }
+        self.push(frame, marker).map(Some)
+    }
+
+    pub fn push_coalesce(&mut self, frame: Frame<MTU>, marker: M) -> Result<Option<usize>, Frame<MTU>> {
+        self.group_seq = self.group_seq.wrapping_add(1);
+        self.heap.push_coalesce(frame, marker, self.group_seq)
+    }
+
+    pub fn contains(&self, frame: &FrameRef) -> bool {
+        self.heap.contains(frame)
+    }
+
+    /// Push frames that are only useful together, if there is not enough space, whole groups of lower
+    /// priority than the group's highest priority member are evicted, so that no group is left partially queued.
+    /// Returns the number of evicted frames or Err if the group doesn't fit.
+    ///
+    /// Frames are buffered on the stack to find the group's highest priority member before anything is evicted,
+    /// which takes `N * size_of::<Option<(Frame<MTU>, M)>>()` bytes. Use
+    /// [push_group_slice](GroupingHeap::push_group_slice) on small stacks, it reads the group twice instead.
+    pub fn push_group(
+        &mut self,
+        frames: impl Iterator<Item = (Frame<MTU>, M)> + ExactSizeIterator
+    ) -> Result<usize, ()> {
+        let count = frames.len();
+        if count > N {
+            return Err(());
+        }
+        let mut group: [Option<(Frame<MTU>, M)>; N] = [None; N];
+        for (slot, item) in group.iter_mut().zip(frames) {
+            *slot = Some(item);
+        }
+        self.push_group_buffered(group.iter().flatten().copied(), count)
+    }
+
+    /// Same as [push_group](GroupingHeap::push_group) for frames that are already in an array, without extra buffering.
+    #[allow(clippy::result_unit_err)]
+    pub fn push_group_slice(&mut self, frames: &[(Frame<MTU>, M)]) -> Result<usize, ()> {
+        self.push_group_buffered(frames.iter().copied(), frames.len())
+    }
+
+    /// Push a group that can be iterated over more than once.
+    fn push_group_buffered(
+        &mut self,
+        frames: impl Iterator<Item = (Frame<MTU>, M)> + Clone,
+        count: usize
+    ) -> Result<usize, ()> {
+        let prio = match frames.clone().map(|(frame, _)| frame.id).min() {
+            Some(prio) => prio,
+            None => return Ok(0)
+        };
+        let removed_items = self.heap.make_room(count, prio)?;
+        self.group_seq = self.group_seq.wrapping_add(1);
+        for (frame, marker) in frames {
+            self.heap.insert(frame, frame.id, marker, self.group_seq, NO_TIMESTAMP, NO_DEADLINE);
+        }
+        if self.heap.sort_on == SortOn::Push {
+            self.heap.sort();
+        }
+
+        Ok(removed_items)
+    }
+
+    /// Same as [push_group](GroupingHeap::push_group), but every frame is scheduled at the priority of the
+    /// group's highest priority member, so the group is popped contiguously, in push order, and is not
+    /// interleaved with unrelated frames. Admission is decided by that priority as well.
+    #[allow(clippy::result_unit_err)]
+    pub fn push_group_atomic(
+        &mut self,
+        frames: impl ExactSizeIterator<Item = (Frame<MTU>, M)> + Clone
+    ) -> Result<usize, ()> {
+        let prio = match frames.clone().map(|(frame, _)| frame.id).min() {
+            Some(prio) => prio,
+            None => return Ok(0)
+        };
+        let removed_items = self.heap.make_room(frames.len(), prio)?;
+        self.group_seq = self.group_seq.wrapping_add(1);
+        for (frame, marker) in frames {
+            self.heap.insert(frame, prio, marker, self.group_seq, NO_TIMESTAMP, NO_DEADLINE);
+        }
+        if self.heap.sort_on == SortOn::Push {
+            self.heap.sort();
+        }
+
+        Ok(removed_items)
+    }
+
+    #[cfg(feature = "deadline")]
+    pub fn push_with_deadline(&mut self, frame: Frame<MTU>, marker: M, deadline: u32) -> Result<usize, Frame<MTU>> {
+        self.group_seq = self.group_seq.wrapping_add(1);
+        self.heap.push_with_deadline(frame, marker, self.group_seq, deadline)
+    }
+
+    #[cfg(feature = "deadline")]
+    pub fn min_slack(&self, now: u32) -> Option<i32> {
+        self.heap.min_slack(now)
+    }
+
+    pub fn push_saturating(&mut self, frame: Frame<MTU>, marker: M) -> bool {
+        self.group_seq = self.group_seq.wrapping_add(1);
+        self.heap.push_saturating(frame, marker, self.group_seq)
+    }
+
+    pub fn rejected_count(&self) -> usize {
+        self.heap.rejected_count()
     }
 
     pub fn pop(&mut self) -> Option<(Frame<MTU>, M)> {
         self.heap.pop()
     }
 
-    pub fn clear(&mut self) {
-        self.heap.clear();
+    #[cfg(feature = "timestamp")]
+    pub fn pop_with_timestamp(&mut self) -> Option<(Frame<MTU>, M, u32)> {
+        self.heap.pop_with_timestamp()
     }
 
-    pub fn len(&self) -> usize {
-        self.heap.len()
+    pub fn peek_marker(&self) -> Option<&M> {
+        self.heap.peek_marker()
     }
-}
 
-pub struct GroupingHeap<M: MarkerTraits, const MTU: usize, const N: usize> {
-    heap: Heap<M, u16, MTU, N>,
-    group_seq: u16,
-}
-impl<M: MarkerTraits, const MTU: usize, const N: usize> GroupingHeap<M, MTU, N> {
-    pub fn new(sort_on: SortOn) -> Self {
-        GroupingHeap {
-            heap: Heap::new(sort_on),
-            group_seq: 0,
-        }
+    pub fn pop_if(&mut self, pred: impl FnOnce(&FrameRef, &M) -> bool) -> Option<(Frame<MTU>, M)> {
+        self.heap.pop_if(pred)
     }
 
-    pub fn push(&mut self, frame: Frame<MTU>, marker: M) -> Result<usize, Frame<MTU>> {
-        self.group_seq = self.group_seq.wrapping_add(1);
-        self.heap.push(frame, marker, self.group_seq)
+    pub fn pop_into(&mut self, out: &mut [(Frame<MTU>, M)]) -> usize {
+        self.heap.pop_into(out)
     }
 
-    pub fn push_group(
+    pub fn iter_ordered<'a>(&'a self, scratch: &'a mut [usize; N]) -> impl Iterator<Item = (FrameRef<'a>, M)> + 'a {
+        self.heap.iter_ordered(scratch)
+    }
+
+    pub fn drain_filter(
         &mut self,
-        mut frames: impl Iterator<Item = (Frame<MTU>, M)> + ExactSizeIterator
-    ) -> Result<usize, ()> {
-        if frames.len() == 0 {
-            return Ok(0);
-        }
-        let frame0 = frames.next().unwrap();
-        let mut removed_items = 0;
-        if N - self.heap.len() < frames.len() {
-            self.heap.data.sort_unstable();
-            self.heap.hint_idx = 0;
-            // frames.len = 3
-            // data for example is:
-            // 0 1 2 3 4 5 6 7 8 9
-            // h h h h m m l l l -
-            // look at N-3   |
-            // if lower, remove same group to the left and everything till the end
-            let new_group_start = N - frames.len();
-            match self.heap.data[new_group_start] {
-                HeapElement::Filled(maybe_lower_priority, _, _, group) => {
-                    if frame0.0 < maybe_lower_priority {
-                        let mut i = new_group_start;
-                        loop {
-                            self.heap.data[i] = HeapElement::Hole;
-                            removed_items += 1;
-                            self.heap.len -= 1;
-                            i = if i > 0 {
-                                i - 1
-                            } else {
-                                break
-                            };
-                            match self.heap.data[i] {
-                                HeapElement::Filled(_, _, _, other_group) => {
-                                    if other_group != group {
-                                        break;
-                                    }
-                                }
-                                HeapElement::Hole => unreachable!()
-                            }
-                            for i in i..N {
-                                self.heap.data[i] = HeapElement::Hole;
-                                self.heap.len -= 1;
-                            }
-                        }
-                    } else {
-                        // will not fit
-                        return Err(());
-                    }
-                }
-                HeapElement::Hole => {
-                    unreachable!();
-                }
-            }
-        }
-        self.group_seq = self.group_seq.wrapping_add(1);
-        let mut i = N - 1;
-        self.heap.data[i] = HeapElement::Filled(frame0.0, self.heap.seq, frame0.1, self.group_seq);
-        self.heap.seq = self.heap.seq.wrapping_add(1);
-        self.heap.len += 1;
-        for frame in frames {
-            self.heap.data[i] = HeapElement::Filled(frame.0, self.heap.seq, frame.1, self.group_seq);
-            self.heap.seq = self.heap.seq.wrapping_add(1);
-            self.heap.len += 1;
-            i -= 1;
-        }
-        if self.heap.sort_on == SortOn::Push {
-            self.heap.data.sort_unstable();
-            self.heap.hint_idx = 0;
-        }
+        pred: impl FnMut(&FrameRef, &M) -> bool,
+        out: &mut impl FnMut(Frame<MTU>, M)
+    ) {
+        self.heap.drain_filter(pred, out);
+    }
 
-        Ok(removed_items)
+    pub fn compact(&mut self) {
+        self.heap.compact();
     }
 
-    pub fn pop(&mut self) -> Option<(Frame<MTU>, M)> {
-        self.heap.pop()
+    pub fn update_markers(&mut self, f: impl FnMut(&FrameRef, &mut M)) {
+        self.heap.update_markers(f)
+    }
+
+    pub fn clear_matching(&mut self, pred: impl Fn(&M) -> bool) -> usize {
+        self.heap.clear_matching(pred)
+    }
+
+    pub fn current_seq(&self) -> Seq {
+        self.heap.current_seq()
+    }
+
+    pub fn iter_with_seq(&self) -> impl Iterator<Item = (FrameRef<'_>, M, Seq)> {
+        self.heap.iter_with_seq()
+    }
+
+    pub fn renumber(&mut self) {
+        self.heap.renumber();
     }
 
     pub fn clear(&mut self) {
@@ -316,6 +1330,15 @@ impl<M: MarkerTraits, const MTU: usize, const N: usize> GroupingHeap<M, MTU, N>
     pub fn len(&self) -> usize {
         self.heap.len()
     }
+
+    pub fn free_slots(&self) -> usize {
+        self.heap.free_slots()
+    }
+
+    /// Whether a group of count frames fits without evicting anything.
+    pub fn can_fit(&self, count: usize) -> bool {
+        count <= self.heap.free_slots()
+    }
 }
 
 #[cfg(test)]
@@ -406,10 +1429,713 @@ mod tests {
         assert_eq!(heap.pop(), None);
     }
 
+    #[test]
+    #[cfg(feature = "timestamp")]
+    fn check_timestamp() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop);
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1, 2, 3]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[4, 5, 6]).unwrap();
+        assert_eq!(heap.push_at(lower_prio, (), 10), Ok(0));
+        assert_eq!(heap.push_at(higher_prio, (), 20), Ok(0));
+        assert_eq!(heap.push(lower_prio, ()), Ok(0));
+
+        assert_eq!(heap.pop_with_timestamp(), Some((higher_prio, (), 20)));
+        assert_eq!(heap.pop_with_timestamp(), Some((lower_prio, (), 10)));
+        assert_eq!(heap.pop_with_timestamp(), Some((lower_prio, (), 0)));
+        assert_eq!(heap.pop_with_timestamp(), None);
+    }
+
+    #[test]
+    fn check_round_robin() {
+        for sort_on_pop in [false, true] {
+            let sort_on = || if sort_on_pop { SortOn::Pop } else { SortOn::Push };
+            let mut heap = Heap::<(), u8, 8, 8>::new(sort_on()).with_fairness(Fairness::RoundRobin);
+            let a = Frame::new(FrameId::new_extended(0x10).unwrap(), &[1]).unwrap();
+            let b = Frame::new(FrameId::new_extended(0x10).unwrap(), &[2]).unwrap();
+            let higher_prio = Frame::new(FrameId::new_extended(0x1).unwrap(), &[0]).unwrap();
+            for _ in 0..3 {
+                assert_eq!(heap.push(a, (), 1), Ok(0));
+            }
+            for _ in 0..3 {
+                assert_eq!(heap.push(b, (), 2), Ok(0));
+            }
+            assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+            assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+            assert_eq!(heap.push(higher_prio, (), 3), Ok(0));
+            assert_eq!(heap.pop().unwrap().0.data(), &[0]);
+            assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+            assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+            assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+            assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+            assert_eq!(heap.pop(), None);
+
+            // Same as strict Lifo without groups
+            let mut heap = PlainHeap::<u8, 8, 8>::new(sort_on()).with_fairness(Fairness::RoundRobin)
+                .with_seq_order(SeqOrder::Lifo);
+            for i in 0..4 {
+                assert_eq!(heap.push(a, i), Ok(0));
+            }
+            for i in (0..4).rev() {
+                assert_eq!(heap.pop(), Some((a, i)));
+            }
+        }
+    }
+
+    #[test]
+    fn check_round_robin_three_groups() {
+        for sort_on_pop in [false, true] {
+            let sort_on = || if sort_on_pop { SortOn::Pop } else { SortOn::Push };
+            let mut heap = Heap::<u8, u8, 8, 8>::new(sort_on()).with_fairness(Fairness::RoundRobin);
+            let frame = Frame::new(FrameId::new_extended(0x10).unwrap(), &[]).unwrap();
+            for group in 1..=3 {
+                for _ in 0..2 {
+                    assert_eq!(heap.push(frame, group, group), Ok(0));
+                }
+            }
+            for group in [1, 2, 3, 1] {
+                assert_eq!(heap.pop(), Some((frame, group)));
+            }
+            // joins the rotation after the groups that are already waiting
+            assert_eq!(heap.push(frame, 4, 4), Ok(0));
+            for group in [2, 3, 4] {
+                assert_eq!(heap.pop(), Some((frame, group)));
+            }
+            assert_eq!(heap.pop(), None);
+
+            let mut heap = Heap::<u8, u8, 8, 8>::new(sort_on()).with_fairness(Fairness::RoundRobin)
+                .with_seq_order(SeqOrder::Lifo);
+            for group in 1..=3 {
+                for _ in 0..2 {
+                    assert_eq!(heap.push(frame, group, group), Ok(0));
+                }
+            }
+            for group in [3, 2, 1, 3, 2, 1] {
+                assert_eq!(heap.pop(), Some((frame, group)));
+            }
+            assert_eq!(heap.pop(), None);
+        }
+    }
+
+    #[test]
+    fn check_renumber() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop);
+        let id = FrameId::new_extended(0x123).unwrap();
+        assert_eq!(heap.push(Frame::new(id, &[1]).unwrap(), ()), Ok(0));
+        heap.heap.seq = 20000;
+        assert_eq!(heap.push(Frame::new(id, &[2]).unwrap(), ()), Ok(0));
+        heap.heap.renumber();
+        assert_eq!(heap.heap.seq, 2);
+        // without renumber this frame would be 40001 pushes apart from the first one and pop before it
+        heap.heap.seq += 20000;
+        assert_eq!(heap.push(Frame::new(id, &[3]).unwrap(), ()), Ok(0));
+
+        assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[3]);
+
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop).with_seq_order(SeqOrder::Lifo);
+        for i in 0..3 {
+            assert_eq!(heap.push(Frame::new(id, &[i]).unwrap(), ()), Ok(0));
+        }
+        heap.heap.renumber();
+        assert_eq!(heap.heap.seq, 3);
+        assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[0]);
+    }
+
+    #[test]
+    fn check_merge() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Push);
+        let mut other = PlainHeap::<(), 8, 8>::new(SortOn::Pop);
+        let lower_prio = FrameId::new_extended(0x123).unwrap();
+        let higher_prio = FrameId::new_extended(0x12).unwrap();
+        assert_eq!(heap.push(Frame::new(lower_prio, &[1]).unwrap(), ()), Ok(0));
+        assert_eq!(heap.push(Frame::new(lower_prio, &[2]).unwrap(), ()), Ok(0));
+        assert_eq!(other.push(Frame::new(lower_prio, &[3]).unwrap(), ()), Ok(0));
+        assert_eq!(other.push(Frame::new(higher_prio, &[4]).unwrap(), ()), Ok(0));
+        assert_eq!(other.push(Frame::new(higher_prio, &[5]).unwrap(), ()), Ok(0));
+
+        assert_eq!(heap.merge(&mut other), 1);
+        assert_eq!(other.len(), 0);
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.pop().unwrap().0.data(), &[4]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[5]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+        assert_eq!(heap.pop(), None);
+
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop).with_seq_order(SeqOrder::Lifo);
+        let mut other = PlainHeap::<(), 8, 4>::new(SortOn::Pop).with_seq_order(SeqOrder::Lifo);
+        assert_eq!(heap.push(Frame::new(lower_prio, &[1]).unwrap(), ()), Ok(0));
+        for i in 2..4 {
+            assert_eq!(other.push(Frame::new(lower_prio, &[i]).unwrap(), ()), Ok(0));
+        }
+        assert_eq!(heap.merge(&mut other), 0);
+        assert_eq!(heap.pop().unwrap().0.data(), &[3]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[1]);
+    }
+
+    #[test]
+    #[cfg(feature = "deadline")]
+    fn check_merge_deadline() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop);
+        let mut other = PlainHeap::<(), 8, 4>::new(SortOn::Pop);
+        let id = FrameId::new_extended(0x123).unwrap();
+        assert_eq!(other.push_with_deadline(Frame::new(id, &[]).unwrap(), (), 100), Ok(0));
+        assert_eq!(heap.merge(&mut other), 0);
+        assert_eq!(heap.min_slack(0), Some(100));
+    }
+
+    #[test]
+    fn check_max_group_eviction() {
+        let lowest_prio = Frame::new(FrameId::new_extended(0x200).unwrap(), &[1]).unwrap();
+        let lower_prio = Frame::new(FrameId::new_extended(0x100).unwrap(), &[2]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[3]).unwrap();
+        let fill = |heap: &mut Heap<(), u8, 8, 4>| {
+            assert_eq!(heap.push(lowest_prio, (), 1), Ok(0));
+            assert_eq!(heap.push(lowest_prio, (), 1), Ok(0));
+            assert_eq!(heap.push(lower_prio, (), 2), Ok(0));
+            assert_eq!(heap.push(lower_prio, (), 2), Ok(0));
+        };
+
+        let mut heap = Heap::<(), u8, 8, 4>::new(SortOn::Push);
+        fill(&mut heap);
+        assert_eq!(heap.push(higher_prio, (), 3), Ok(2));
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.pop().unwrap().0.data(), &[3]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+        assert_eq!(heap.pop().unwrap().0.data(), &[2]);
+        assert_eq!(heap.pop(), None);
+
+        let mut heap = Heap::<(), u8, 8, 4>::new(SortOn::Push).with_max_group_eviction(1);
+        fill(&mut heap);
+        assert_eq!(heap.push(higher_prio, (), 3), Err(higher_prio));
+        assert_eq!(heap.len(), 4);
+
+        let group = [(higher_prio, ()), (higher_prio, ())];
+        let mut heap = GroupingHeap::<(), 8, 4>::new(SortOn::Push).with_max_group_eviction(1);
+        assert_eq!(heap.push_group_slice(&[(lowest_prio, ()); 4]), Ok(0));
+        assert_eq!(heap.push_group_slice(&group[..1]), Err(()));
+        assert_eq!(heap.len(), 4);
+
+        let mut heap = GroupingHeap::<(), 8, 4>::new(SortOn::Push).with_max_group_eviction(2);
+        for frame in [lowest_prio, lowest_prio, lower_prio, lower_prio] {
+            assert_eq!(heap.push(frame, ()), Ok(0));
+        }
+        assert_eq!(heap.push_group_slice(&group), Ok(2));
+        assert_eq!(heap.len(), 4);
+    }
+
+    #[test]
+    fn check_iter_ordered() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let lower_prio = FrameId::new_extended(0x123).unwrap();
+        let higher_prio = FrameId::new_extended(0x12).unwrap();
+        assert_eq!(heap.push(Frame::new(lower_prio, &[1]).unwrap(), 1), Ok(0));
+        assert_eq!(heap.push(Frame::new(higher_prio, &[2]).unwrap(), 2), Ok(0));
+        assert_eq!(heap.push(Frame::new(lower_prio, &[3]).unwrap(), 3), Ok(0));
+
+        let mut scratch = [0usize; 4];
+        let mut iter = heap.iter_ordered(&mut scratch);
+        assert_eq!(iter.next(), Some((FrameRef { id: higher_prio, data: &[2] }, 2)));
+        assert_eq!(iter.next(), Some((FrameRef { id: lower_prio, data: &[1] }, 1)));
+        assert_eq!(iter.next(), Some((FrameRef { id: lower_prio, data: &[3] }, 3)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(heap.len(), 3);
+    }
+
+    #[test]
+    fn check_dedup() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Push);
+        let status = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1, 2, 3]).unwrap();
+        let status_changed = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1, 2, 4]).unwrap();
+        assert!(!heap.contains(&status.as_frame_ref()));
+        assert_eq!(heap.push_dedup(status, ()), Ok(Some(0)));
+        assert!(heap.contains(&status.as_frame_ref()));
+        assert_eq!(heap.push_dedup(status, ()), Ok(None));
+        assert_eq!(heap.push_dedup(status_changed, ()), Ok(Some(0)));
+        assert_eq!(heap.len(), 2);
+    }
+
+    #[test]
+    fn check_is_sorted() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop);
+        assert!(heap.heap.is_sorted());
+        assert_eq!(heap.push(Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap(), ()), Ok(0));
+        assert_eq!(heap.push(Frame::new(FrameId::new_extended(0x12).unwrap(), &[]).unwrap(), ()), Ok(0));
+        assert!(!heap.heap.is_sorted());
+        heap.heap.sort();
+        assert!(heap.heap.is_sorted());
+    }
+
+    #[test]
+    #[cfg(feature = "deadline")]
+    fn check_min_slack() {
+        let mut heap = PlainHeap::<(), 8, 4>::new(SortOn::Pop);
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        let now = u32::MAX - 5;
+        assert_eq!(heap.min_slack(now), None);
+        assert_eq!(heap.push(frame, ()), Ok(0));
+        assert_eq!(heap.min_slack(now), None);
+        assert_eq!(heap.push_with_deadline(frame, (), now.wrapping_add(20)), Ok(0));
+        assert_eq!(heap.push_with_deadline(frame, (), now.wrapping_add(10)), Ok(0));
+        assert_eq!(heap.min_slack(now), Some(10));
+        assert_eq!(heap.min_slack(now.wrapping_add(15)), Some(-5));
+    }
+
+    #[test]
+    fn check_push_saturating() {
+        let mut heap = PlainHeap::<u8, 8, 2>::new(SortOn::Push);
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[]).unwrap();
+        assert!(heap.push_saturating(lower_prio, 1));
+        assert!(heap.push_saturating(lower_prio, 2));
+        assert!(!heap.push_saturating(higher_prio, 3));
+        assert!(!heap.push_saturating(higher_prio, 4));
+        assert_eq!(heap.rejected_count(), 2);
+        assert_eq!(heap.pop(), Some((lower_prio, 1)));
+        assert!(heap.push_saturating(higher_prio, 5));
+        assert_eq!(heap.pop(), Some((higher_prio, 5)));
+        assert_eq!(heap.rejected_count(), 2);
+    }
+
+    #[test]
+    fn check_coalesce() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let status = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1]).unwrap();
+        let status_changed = Frame::new(FrameId::new_extended(0x123).unwrap(), &[2]).unwrap();
+        let other = Frame::new(FrameId::new_extended(0x12).unwrap(), &[3]).unwrap();
+        assert_eq!(heap.push_coalesce(status, 1), Ok(Some(0)));
+        assert_eq!(heap.push_coalesce(other, 2), Ok(Some(0)));
+        assert_eq!(heap.push_coalesce(status_changed, 3), Ok(None));
+        assert_eq!(heap.len(), 2);
+        assert_eq!(heap.pop(), Some((other, 2)));
+        assert_eq!(heap.pop(), Some((status_changed, 3)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_peek_marker() {
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[2]).unwrap();
+        for sort_on_pop in [false, true] {
+            let sort_on = if sort_on_pop { SortOn::Pop } else { SortOn::Push };
+            let mut heap = PlainHeap::<u8, 8, 4>::new(sort_on);
+            assert_eq!(heap.peek_marker(), None);
+            assert_eq!(heap.push(lower_prio, 1), Ok(0));
+            assert_eq!(heap.peek_marker(), Some(&1));
+            assert_eq!(heap.push(higher_prio, 2), Ok(0));
+            assert_eq!(heap.push(higher_prio, 3), Ok(0));
+            assert_eq!(heap.peek_marker(), Some(&2));
+            assert_eq!(heap.pop(), Some((higher_prio, 2)));
+            assert_eq!(heap.peek_marker(), Some(&3));
+            assert_eq!(heap.pop(), Some((higher_prio, 3)));
+            assert_eq!(heap.peek_marker(), Some(&1));
+        }
+    }
+
+    #[test]
+    fn check_pop_if() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[2]).unwrap();
+        assert_eq!(heap.push(lower_prio, 1), Ok(0));
+        assert_eq!(heap.push(higher_prio, 2), Ok(0));
+        assert_eq!(heap.pop_if(|_, marker| *marker == 1), None);
+        assert_eq!(heap.len(), 2);
+        assert_eq!(heap.pop_if(|frame, _| frame.id == higher_prio.id), Some((higher_prio, 2)));
+        assert_eq!(heap.pop_if(|_, _| true), Some((lower_prio, 1)));
+        assert_eq!(heap.pop_if(|_, _| true), None);
+    }
+
+    #[test]
+    fn check_pop_into() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[2]).unwrap();
+        assert_eq!(heap.push(lower_prio, 1), Ok(0));
+        assert_eq!(heap.push(higher_prio, 2), Ok(0));
+        assert_eq!(heap.push(lower_prio, 3), Ok(0));
+
+        let mut out = [(lower_prio, 0); 2];
+        assert_eq!(heap.pop_into(&mut out), 2);
+        assert_eq!(out, [(higher_prio, 2), (lower_prio, 1)]);
+        assert_eq!(heap.pop_into(&mut out), 1);
+        assert_eq!(out[0], (lower_prio, 3));
+        assert_eq!(heap.pop_into(&mut out), 0);
+        assert_eq!(heap.len(), 0);
+    }
+
+    #[test]
+    fn check_stable_sort() {
+        let mut heap = PlainHeap::<u8, 8, 8>::new(SortOn::Pop).with_stable_sort(true);
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[1]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[2]).unwrap();
+        for i in 0..4 {
+            assert_eq!(heap.push(lower_prio, i), Ok(0));
+            assert_eq!(heap.push(higher_prio, i), Ok(0));
+        }
+        for i in 0..4 {
+            assert_eq!(heap.pop(), Some((higher_prio, i)));
+        }
+        for i in 0..4 {
+            assert_eq!(heap.pop(), Some((lower_prio, i)));
+        }
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_drain_filter() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Push);
+        let node_a = Frame::new(FrameId::new_extended(0x10).unwrap(), &[1]).unwrap();
+        let node_b = Frame::new(FrameId::new_extended(0x20).unwrap(), &[2]).unwrap();
+        assert_eq!(heap.push(node_a, 0), Ok(0));
+        assert_eq!(heap.push(node_b, 1), Ok(0));
+        assert_eq!(heap.push(node_a, 2), Ok(0));
+        assert_eq!(heap.pop(), Some((node_a, 0)));
+
+        let mut removed = 0;
+        heap.drain_filter(|frame, _| frame.id == node_a.id, &mut |frame, marker| {
+            assert_eq!((frame, marker), (node_a, 2));
+            removed += 1;
+        });
+        assert_eq!(removed, 1);
+        assert_eq!(heap.len(), 1);
+        assert_eq!(heap.pop(), Some((node_b, 1)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_group_atomic() {
+        let id = |id| FrameId::new_extended(id).unwrap();
+        for atomic in [false, true] {
+            let mut heap = GroupingHeap::<u8, 8, 4>::new(SortOn::Push);
+            assert_eq!(heap.push(Frame::new(id(0x50), &[]).unwrap(), 0), Ok(0));
+            let group = [
+                (Frame::new(id(0x100), &[]).unwrap(), 1),
+                (Frame::new(id(0x10), &[]).unwrap(), 2),
+                (Frame::new(id(0x200), &[]).unwrap(), 3),
+            ];
+            if atomic {
+                assert_eq!(heap.push_group_atomic(group.iter().cloned()), Ok(0));
+            } else {
+                assert_eq!(heap.push_group(group.iter().cloned()), Ok(0));
+            }
+            let mut order = [0u8; 4];
+            for marker in order.iter_mut() {
+                *marker = heap.pop().unwrap().1;
+            }
+            if atomic {
+                assert_eq!(order, [1, 2, 3, 0]);
+            } else {
+                assert_eq!(order, [2, 0, 1, 3]);
+            }
+        }
+
+        // shared priority is kept when merging
+        let mut heap = GroupingHeap::<u8, 8, 4>::new(SortOn::Push);
+        let mut other = GroupingHeap::<u8, 8, 4>::new(SortOn::Push);
+        assert_eq!(heap.push(Frame::new(id(0x50), &[]).unwrap(), 0), Ok(0));
+        let group = [(Frame::new(id(0x100), &[]).unwrap(), 1), (Frame::new(id(0x10), &[]).unwrap(), 2)];
+        assert_eq!(other.push_group_atomic(group.iter().cloned()), Ok(0));
+        assert_eq!(heap.heap.merge(&mut other.heap), 0);
+        for marker in [1, 2, 0] {
+            assert_eq!(heap.pop().unwrap().1, marker);
+        }
+    }
+
+    #[test]
+    fn check_group_eviction() {
+        let id = |id| FrameId::new_extended(id).unwrap();
+        let mut heap = GroupingHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let low = [(Frame::new(id(0x300), &[]).unwrap(), 1), (Frame::new(id(0x20), &[]).unwrap(), 1)];
+        assert_eq!(heap.push_group(low.iter().cloned()), Ok(0));
+        assert_eq!(heap.push(Frame::new(id(0x200), &[]).unwrap(), 2), Ok(0));
+        assert!(heap.can_fit(1));
+        assert_eq!(heap.push(Frame::new(id(0x50), &[]).unwrap(), 3), Ok(0));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.free_slots(), 0);
+        assert!(!heap.can_fit(1));
+
+        let not_fitting = [(Frame::new(id(0x250), &[]).unwrap(), 4); 4];
+        assert_eq!(heap.push_group(not_fitting.iter().cloned()), Err(()));
+        assert_eq!(heap.push_group(not_fitting[..3].iter().cloned()), Err(()));
+        assert_eq!(heap.len(), 4);
+
+        // evicts the low group entirely, including it's 0x20 member
+        let high = [(Frame::new(id(0x100), &[]).unwrap(), 5), (Frame::new(id(0x100), &[]).unwrap(), 6)];
+        assert_eq!(heap.push_group(high.iter().cloned()), Ok(2));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.pop().unwrap().1, 3);
+        assert_eq!(heap.pop().unwrap().1, 5);
+        assert_eq!(heap.pop().unwrap().1, 6);
+        assert_eq!(heap.pop().unwrap().1, 2);
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_group_admission_by_best_member() {
+        let id = |id| FrameId::new_extended(id).unwrap();
+        let mut heap = GroupingHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let queued = [(Frame::new(id(0x200), &[]).unwrap(), 1), (Frame::new(id(0x201), &[]).unwrap(), 1)];
+        assert_eq!(heap.push_group(queued.iter().cloned()), Ok(0));
+        let queued = [(Frame::new(id(0x202), &[]).unwrap(), 2), (Frame::new(id(0x203), &[]).unwrap(), 2)];
+        assert_eq!(heap.push_group(queued.iter().cloned()), Ok(0));
+
+        // led by a lower priority frame than any queued one, but it's 0x100 member outranks them
+        let group = [(Frame::new(id(0x300), &[]).unwrap(), 3), (Frame::new(id(0x100), &[]).unwrap(), 3)];
+        assert_eq!(heap.push_group(group.iter().cloned()), Ok(2));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.pop().unwrap().1, 3);
+        assert_eq!(heap.pop().unwrap().1, 1);
+        assert_eq!(heap.pop().unwrap().1, 1);
+        assert_eq!(heap.pop().unwrap().1, 3);
+        assert_eq!(heap.pop(), None);
+
+        // lazy producer is consumed only once
+        let mut produced = 0;
+        let group = (0..2).map(|_| {
+            produced += 1;
+            (Frame::new(id(0x50), &[]).unwrap(), 4)
+        });
+        assert_eq!(heap.push_group(group), Ok(0));
+        assert_eq!(produced, 2);
+        assert_eq!(heap.len(), 2);
+    }
+
+    #[test]
+    fn check_on_full() {
+        let low_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        let high_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[]).unwrap();
+
+        let mut heap = PlainHeap::<u8, 8, 3>::new(SortOn::Push).with_on_full(OnFull::Reject);
+        for i in 0..3 {
+            assert_eq!(heap.push(low_prio, i), Ok(0));
+        }
+        assert_eq!(heap.push(high_prio, 3), Err(high_prio));
+        assert_eq!(heap.len(), 3);
+
+        let mut heap = PlainHeap::<u8, 8, 3>::new(SortOn::Push).with_on_full(OnFull::EvictOldest);
+        assert_eq!(heap.push(high_prio, 0), Ok(0));
+        assert_eq!(heap.push(low_prio, 1), Ok(0));
+        assert_eq!(heap.push(high_prio, 2), Ok(0));
+        assert_eq!(heap.push(low_prio, 3), Ok(1));
+        assert_eq!(heap.push(low_prio, 4), Ok(1));
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.pop(), Some((high_prio, 2)));
+        assert_eq!(heap.pop(), Some((low_prio, 3)));
+        assert_eq!(heap.pop(), Some((low_prio, 4)));
+        assert_eq!(heap.pop(), None);
+
+        let group = [(high_prio, 4), (high_prio, 5)];
+        let mut heap = GroupingHeap::<u8, 8, 3>::new(SortOn::Push).with_on_full(OnFull::Reject);
+        for i in 0..2 {
+            assert_eq!(heap.push(low_prio, i), Ok(0));
+        }
+        assert_eq!(heap.push_group_slice(&group), Err(()));
+        assert_eq!(heap.len(), 2);
+
+        let mut heap = GroupingHeap::<u8, 8, 3>::new(SortOn::Push).with_on_full(OnFull::EvictOldest);
+        assert_eq!(heap.push(high_prio, 0), Ok(0));
+        assert_eq!(heap.push(low_prio, 1), Ok(0));
+        assert_eq!(heap.push(low_prio, 2), Ok(0));
+        assert_eq!(heap.push_group_slice(&group), Ok(2));
+        assert_eq!(heap.pop(), Some((high_prio, 4)));
+        assert_eq!(heap.pop(), Some((high_prio, 5)));
+        assert_eq!(heap.pop(), Some((low_prio, 2)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_iter_with_seq() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        assert_eq!(heap.current_seq(), 0);
+        assert_eq!(heap.push(frame, 1), Ok(0));
+        assert_eq!(heap.push(frame, 2), Ok(0));
+        assert_eq!(heap.current_seq(), 2);
+        assert_eq!(heap.pop(), Some((frame, 1)));
+        let mut iter = heap.iter_with_seq();
+        assert_eq!(iter.next(), Some((frame.as_frame_ref(), 2, 1)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn check_update_markers() {
+        let mut heap = PlainHeap::<u8, 8, 4>::new(SortOn::Pop);
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[]).unwrap();
+        assert_eq!(heap.push(lower_prio, 1), Ok(0));
+        assert_eq!(heap.push(higher_prio, 2), Ok(0));
+        assert_eq!(heap.push(lower_prio, 1), Ok(0));
+        heap.update_markers(|_, ttl| *ttl -= 1);
+        assert_eq!(heap.clear_matching(|ttl| *ttl == 0), 2);
+        heap.update_markers(|frame, ttl| if frame.id == higher_prio.id { *ttl += 10 });
+        assert_eq!(heap.pop(), Some((higher_prio, 11)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_group_keys_dont_alias_at_u16() {
+        let mut heap = GroupingHeap::<(), 8, 2>::new(SortOn::Pop);
+        let frame = |id| Frame::new(FrameId::new_standard(id).unwrap(), &[]).unwrap();
+        assert_eq!(heap.push(frame(0x300), ()), Ok(0));
+        heap.group_seq += 0x10000;
+        assert_eq!(heap.push(frame(0x200), ()), Ok(0));
+        assert_eq!(heap.push(frame(0x100), ()), Ok(1));
+        assert_eq!(heap.len(), 2);
+    }
+
+    #[test]
+    fn check_clear_matching() {
+        let mut heap = GroupingHeap::<u8, 8, 8>::new(SortOn::Pop);
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        for node in [1, 2, 1, 3, 1] {
+            assert_eq!(heap.push(frame, node), Ok(0));
+        }
+        assert_eq!(heap.clear_matching(|node| *node == 1), 3);
+        assert_eq!(heap.clear_matching(|node| *node == 1), 0);
+        assert_eq!(heap.len(), 2);
+        assert_eq!(heap.pop(), Some((frame, 2)));
+        assert_eq!(heap.pop(), Some((frame, 3)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_compact() {
+        let mut heap = PlainHeap::<u8, 8, 8>::new(SortOn::Push);
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        for i in 0..6 {
+            assert_eq!(heap.push(frame, i), Ok(0));
+        }
+        assert_eq!(heap.pop(), Some((frame, 0)));
+        assert_eq!(heap.pop(), Some((frame, 1)));
+        heap.drain_filter(|_, marker| *marker == 3, &mut |_, _| {});
+        heap.compact();
+        assert_eq!(heap.heap.hint_idx, 0);
+        assert!(heap.heap.data[..3].iter().all(|elem| *elem != HeapElement::Hole));
+        assert!(heap.heap.data[3..].iter().all(|elem| *elem == HeapElement::Hole));
+        assert_eq!(heap.pop(), Some((frame, 2)));
+        assert_eq!(heap.pop(), Some((frame, 4)));
+        assert_eq!(heap.pop(), Some((frame, 5)));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn check_footprint() {
+        const _: () = assert!(Heap::<(), u8, 8, 32>::footprint() <= 2048);
+        assert_eq!(Heap::<(), u8, 8, 32>::footprint(), core::mem::size_of::<Heap<(), u8, 8, 32>>());
+        assert!(PlainHeap::<(), 8, 32>::footprint() >= core::mem::size_of::<Frame<8>>() * 32);
+        assert!(GroupingHeap::<(), 8, 4>::footprint() < GroupingHeap::<(), 8, 8>::footprint());
+    }
+
+    #[test]
+    fn check_snapshot() {
+        let mut heap = Heap::<u8, u8, 8, 4>::new(SortOn::Pop);
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        assert_eq!(heap.push(frame, 0, 0), Ok(0));
+        assert_eq!(heap.push(frame, 1, 0), Ok(0));
+        let snap = heap.snapshot();
+        assert_eq!(heap.pop(), Some((frame, 0)));
+        assert!(heap.snapshot() != snap);
+        heap.restore(&snap);
+        assert!(heap.snapshot() == snap);
+        assert_eq!(heap.len(), 2);
+        assert_eq!(heap.pop(), Some((frame, 0)));
+        assert_eq!(heap.pop(), Some((frame, 1)));
+
+        let mut heap = Heap::<u8, u8, 8, 1>::new(SortOn::Pop);
+        assert!(heap.push_saturating(frame, 0, 0));
+        let snap = heap.snapshot();
+        assert!(!heap.push_saturating(frame, 1, 0));
+        assert_eq!(heap.rejected_count(), 1);
+        heap.restore(&snap);
+        assert_eq!(heap.rejected_count(), 0);
+    }
+
+    #[test]
+    fn check_seq_order() {
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        for sort_on_pop in [false, true] {
+            let sort_on = || if sort_on_pop { SortOn::Pop } else { SortOn::Push };
+            let mut fifo = PlainHeap::<u8, 8, 4>::new(sort_on()).with_seq_order(SeqOrder::Fifo);
+            let mut lifo = PlainHeap::<u8, 8, 4>::new(sort_on()).with_seq_order(SeqOrder::Lifo);
+            for i in 0..3 {
+                assert_eq!(fifo.push(frame, i), Ok(0));
+                assert_eq!(lifo.push(frame, i), Ok(0));
+            }
+            for i in 0..3 {
+                assert_eq!(fifo.pop(), Some((frame, i)));
+                assert_eq!(lifo.pop(), Some((frame, 2 - i)));
+            }
+        }
+    }
+
+    #[test]
+    fn check_total_order() {
+        let id = FrameId::new_extended(0x123).unwrap();
+        let a: HeapElement<(), (), 8> = HeapElement::Filled {
+            frame: Frame::new(id, &[1]).unwrap(), prio: id, seq: 0, marker: (), group: (), ts: NO_TIMESTAMP, deadline: NO_DEADLINE
+        };
+        let b: HeapElement<(), (), 8> = HeapElement::Filled {
+            frame: Frame::new(id, &[2]).unwrap(), prio: id, seq: 0, marker: (), group: (), ts: NO_TIMESTAMP, deadline: NO_DEADLINE
+        };
+        // equal seqs, ordered by payload
+        for seq_order in [SeqOrder::Fifo, SeqOrder::Lifo] {
+            assert_eq!(a.cmp_ordered(&b, seq_order, 0), Ordering::Less);
+            assert_eq!(b.cmp_ordered(&a, seq_order, 0), Ordering::Greater);
+            assert_eq!(a.cmp_ordered(&a, seq_order, 0), Ordering::Equal);
+        }
+        // older first with Fifo, by age relative to now, even across the wraparound
+        let a = a.with_seq(Seq::MAX);
+        let b = b.with_seq(Seq::MIN);
+        assert_eq!(a.cmp_ordered(&b, SeqOrder::Fifo, Seq::MIN.wrapping_add(1)), Ordering::Less);
+        assert_eq!(a.cmp_ordered(&b, SeqOrder::Lifo, Seq::MIN.wrapping_add(1)), Ordering::Greater);
+        assert_eq!(a.cmp_ordered(&HeapElement::Hole, SeqOrder::Fifo, 0), Ordering::Less);
+    }
+
+    #[test]
+    fn check_seq_wraparound_stress() {
+        // more than 2^15 same ID pushes interleaved with pops, with Lifo the first frames stay queued all along,
+        // so resident seqs end up spanning more than half of the i16 range
+        let frame = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        for sort_on_pop in [false, true] {
+            let sort_on = || if sort_on_pop { SortOn::Pop } else { SortOn::Push };
+            let mut fifo = PlainHeap::<u32, 8, 8>::new(sort_on());
+            let mut lifo = PlainHeap::<u32, 8, 8>::new(sort_on()).with_seq_order(SeqOrder::Lifo);
+            for i in 0..4 {
+                assert_eq!(fifo.push(frame, i), Ok(0));
+                assert_eq!(lifo.push(frame, i), Ok(0));
+            }
+            let mut pushed = 4;
+            let mut fifo_next = 0;
+            while pushed < 40000 {
+                for _ in 0..3 {
+                    assert_eq!(fifo.push(frame, pushed), Ok(0));
+                    assert_eq!(lifo.push(frame, pushed), Ok(0));
+                    pushed += 1;
+                }
+                for i in 1..=3 {
+                    assert_eq!(fifo.pop(), Some((frame, fifo_next)));
+                    fifo_next += 1;
+                    assert_eq!(lifo.pop(), Some((frame, pushed - i)));
+                }
+            }
+            for i in (0..4).rev() {
+                assert_eq!(fifo.pop(), Some((frame, fifo_next)));
+                fifo_next += 1;
+                assert_eq!(lifo.pop(), Some((frame, i)));
+            }
+        }
+    }
+
     #[test]
     fn check_grouping() {
         let mut heap = GroupingHeap::<(), 8, 4>::new(SortOn::Push);
         let group1 = &mut [(Frame::new(FrameId::new_extended(0x123).unwrap(), &[1, 2, 3]).unwrap(), ())];
-        heap.push_group(group1);
+        assert_eq!(heap.push_group_slice(group1), Ok(0));
+        assert_eq!(heap.len(), 1);
     }
 }
\ No newline at end of file
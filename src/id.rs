@@ -8,6 +8,9 @@ use core::fmt::{Debug, Formatter};
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct StandardId(u16);
 impl StandardId {
+    pub const ZERO: StandardId = StandardId(0);
+    pub const MAX: StandardId = StandardId(crate::STANDARD_ID_ALL_BITS);
+
     pub const fn new(standard_id: u16) -> Option<StandardId> {
         if standard_id & (0b0001_1111 << 11) != 0 {
             None
@@ -16,6 +19,15 @@ impl StandardId {
         }
     }
 
+    /// Panics if standard_id is out of range, which is a compile time error in const context:
+    /// `const ID: StandardId = StandardId::new_or_panic(0x123);`
+    pub const fn new_or_panic(standard_id: u16) -> StandardId {
+        match StandardId::new(standard_id) {
+            Some(id) => id,
+            None => panic!("standard id is out of range")
+        }
+    }
+
     pub fn inner(&self) -> u16 {
         self.0
     }
@@ -23,12 +35,33 @@ impl StandardId {
     pub unsafe fn new_unchecked(standard_id: u16) -> StandardId {
         StandardId(standard_id)
     }
+
+    /// Builds CANopen COB-ID, None if function_code > 0xF or node_id > 0x7F.
+    pub const fn from_canopen(function_code: u8, node_id: u8) -> Option<StandardId> {
+        if function_code > 0xF || node_id > 0x7F {
+            return None;
+        }
+        Some(StandardId((function_code as u16) << 7 | node_id as u16))
+    }
+
+    /// CANopen function code, upper 4 bits of the ID.
+    pub fn function_code(&self) -> u8 {
+        (self.0 >> 7) as u8
+    }
+
+    /// CANopen node ID, lower 7 bits of the ID.
+    pub fn node_id(&self) -> u8 {
+        (self.0 & 0x7F) as u8
+    }
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct ExtendedId(u32);
 impl ExtendedId {
+    pub const ZERO: ExtendedId = ExtendedId(0);
+    pub const MAX: ExtendedId = ExtendedId(crate::EXTENDED_ID_ALL_BITS);
+
     pub const fn new(extended_id: u32) -> Option<ExtendedId> {
         if extended_id & (0b111 << 29) != 0 {
             None
@@ -37,6 +70,15 @@ impl ExtendedId {
         }
     }
 
+    /// Panics if extended_id is out of range, which is a compile time error in const context:
+    /// `const ID: ExtendedId = ExtendedId::new_or_panic(0x123);`
+    pub const fn new_or_panic(extended_id: u32) -> ExtendedId {
+        match ExtendedId::new(extended_id) {
+            Some(id) => id,
+            None => panic!("extended id is out of range")
+        }
+    }
+
     pub fn inner(&self) -> u32 {
         self.0
     }
@@ -44,6 +86,26 @@ impl ExtendedId {
     pub unsafe fn new_unchecked(extended_id: u32) -> ExtendedId {
         ExtendedId(extended_id)
     }
+
+    /// Builds J1939 PDU1 (peer to peer) id, None if priority > 7 or pf >= 240 (PDU2 format).
+    pub const fn from_j1939_pdu1(priority: u8, pf: u8, da: u8, sa: u8) -> Option<ExtendedId> {
+        if priority > 7 || pf >= 240 {
+            return None;
+        }
+        Some(ExtendedId(
+            (priority as u32) << 26 | (pf as u32) << 16 | (da as u32) << 8 | sa as u32
+        ))
+    }
+
+    /// J1939 destination address, only present in PDU1 format messages (pf < 240).
+    pub fn destination_address(&self) -> Option<u8> {
+        let pf = (self.0 >> 16) as u8;
+        if pf < 240 {
+            Some((self.0 >> 8) as u8)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Hash)]
@@ -70,6 +132,134 @@ impl FrameId {
             None => None
         }
     }
+
+    pub const fn new_standard_or_panic(standard_id: u16) -> FrameId {
+        FrameId::Standard(StandardId::new_or_panic(standard_id))
+    }
+
+    pub const fn new_extended_or_panic(extended_id: u32) -> FrameId {
+        FrameId::Extended(ExtendedId::new_or_panic(extended_id))
+    }
+
+    /// All ones standard ID (0x7FF), which is also the lowest priority one.
+    pub const fn broadcast_standard() -> FrameId {
+        FrameId::Standard(StandardId::MAX)
+    }
+
+    /// All ones extended ID (0x1FFFFFFF), which is also the lowest priority one.
+    pub const fn broadcast_extended() -> FrameId {
+        FrameId::Extended(ExtendedId::MAX)
+    }
+
+    /// Best effort classification for formats that don't carry the IDE flag (e.g. candump logs without it):
+    /// standard if raw fits into 11 bits, extended otherwise, None if it doesn't fit into 29 bits.
+    /// This is lossy, extended IDs <= 0x7FF are misclassified as standard,
+    /// use [from_raw_auto_ext](FrameId::from_raw_auto_ext) when the ID is known to be extended.
+    pub const fn from_raw_auto(raw: u32) -> Option<FrameId> {
+        if raw <= crate::STANDARD_ID_ALL_BITS as u32 {
+            FrameId::new_standard(raw as u16)
+        } else {
+            FrameId::new_extended(raw)
+        }
+    }
+
+    /// Counterpart of [from_raw_auto](FrameId::from_raw_auto), that always builds an extended ID.
+    pub const fn from_raw_auto_ext(raw: u32) -> Option<FrameId> {
+        FrameId::new_extended(raw)
+    }
+
+    /// Same as `==`, but usable in const context.
+    pub const fn eq_const(&self, other: &FrameId) -> bool {
+        match (self, other) {
+            (FrameId::Standard(l), FrameId::Standard(r)) => l.0 == r.0,
+            (FrameId::Extended(l), FrameId::Extended(r)) => l.0 == r.0,
+            _ => false
+        }
+    }
+
+    /// Same ordering as [Ord], but usable in const context: -1 for less, 0 for equal and 1 for greater.
+    pub const fn cmp_const(&self, other: &FrameId) -> i8 {
+        const fn cmp(l: u32, r: u32) -> i8 {
+            if l < r {
+                -1
+            } else if l > r {
+                1
+            } else {
+                0
+            }
+        }
+        match (self, other) {
+            (FrameId::Standard(l), FrameId::Standard(r)) => cmp(l.0 as u32, r.0 as u32),
+            (FrameId::Extended(l), FrameId::Extended(r)) => cmp(l.0, r.0),
+            // Standard frame wins because of IDE dominant
+            (FrameId::Standard(_), FrameId::Extended(_)) => -1,
+            (FrameId::Extended(_), FrameId::Standard(_)) => 1
+        }
+    }
+
+    /// 11 bits that compete in the first arbitration phase: standard ID itself or bits 28..18 of an extended one.
+    pub fn base_id(&self) -> u16 {
+        match self {
+            FrameId::Standard(sid) => sid.0,
+            FrameId::Extended(eid) => (eid.0 >> 18) as u16
+        }
+    }
+
+    /// Compare against a raw ID value straight from hardware, without constructing and validating a FrameId.
+    pub fn eq_raw(&self, raw: u32, extended: bool) -> bool {
+        match self {
+            FrameId::Standard(sid) => !extended && raw == sid.0 as u32,
+            FrameId::Extended(eid) => extended && raw == eid.0
+        }
+    }
+
+    /// Whether ID is not a J1939 one: extended with Extended Data Page bit (25) set, which J1939 reserves
+    /// (or leaves to ISO 15765-3). Standard IDs are not J1939 IDs at all and are not considered reserved.
+    pub fn is_reserved_j1939(&self) -> bool {
+        match self {
+            FrameId::Standard(_) => false,
+            FrameId::Extended(eid) => eid.0 & (1 << 25) != 0
+        }
+    }
+
+    /// Whether raw ID value is within lo..=hi, regardless of it's kind.
+    pub fn is_in_range(&self, lo: u32, hi: u32) -> bool {
+        let raw = match self {
+            FrameId::Standard(sid) => sid.0 as u32,
+            FrameId::Extended(eid) => eid.0
+        };
+        lo <= raw && raw <= hi
+    }
+
+    /// Big endian ID bytes and the number of significant ones (2 for standard and 4 for extended ID),
+    /// significant bytes are at the beginning of the array.
+    pub fn to_be_bytes(self) -> ([u8; 4], usize) {
+        let mut bytes = [0u8; 4];
+        match self {
+            FrameId::Standard(sid) => {
+                bytes[0..2].copy_from_slice(&sid.0.to_be_bytes());
+                (bytes, 2)
+            }
+            FrameId::Extended(eid) => {
+                (eid.0.to_be_bytes(), 4)
+            }
+        }
+    }
+
+    /// Little endian ID bytes and the number of significant ones (2 for standard and 4 for extended ID),
+    /// significant bytes are at the beginning of the array.
+    pub fn to_le_bytes(self) -> ([u8; 4], usize) {
+        let mut bytes = [0u8; 4];
+        match self {
+            FrameId::Standard(sid) => {
+                bytes[0..2].copy_from_slice(&sid.0.to_le_bytes());
+                (bytes, 2)
+            }
+            FrameId::Extended(eid) => {
+                (eid.0.to_le_bytes(), 4)
+            }
+        }
+    }
 }
 impl Ord for FrameId {
     fn cmp(&self, other: &Self) -> Ordering {
@@ -122,17 +312,87 @@ impl Debug for FrameId {
         }
     }
 }
+/// Prints `0x123`.
+impl fmt::Display for StandardId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:#05X}", self.0)
+    }
+}
+/// Prints `0x00000123`.
+impl fmt::Display for ExtendedId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:#010X}", self.0)
+    }
+}
+/// Zero padded to 3 digits: `{:X}` prints `12A`, `{:#X}` prints `0x12A`.
+impl fmt::UpperHex for StandardId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#05X}", self.0)
+        } else {
+            write!(f, "{:03X}", self.0)
+        }
+    }
+}
+impl fmt::LowerHex for StandardId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#05x}", self.0)
+        } else {
+            write!(f, "{:03x}", self.0)
+        }
+    }
+}
+/// Zero padded to 8 digits: `{:X}` prints `0000012A`, `{:#X}` prints `0x0000012A`.
+impl fmt::UpperHex for ExtendedId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#010X}", self.0)
+        } else {
+            write!(f, "{:08X}", self.0)
+        }
+    }
+}
+impl fmt::LowerHex for ExtendedId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#010x}", self.0)
+        } else {
+            write!(f, "{:08x}", self.0)
+        }
+    }
+}
 impl hash32::Hash for FrameId {
     fn hash<H: hash32::Hasher>(&self, state: &mut H) {
-        match *self {
-            FrameId::Standard(sid) => {
-                state.write(&sid.0.to_le_bytes())
-            }
-            FrameId::Extended(eid) => {
-                state.write(&eid.0.to_le_bytes())
-            }
+        let (bytes, len) = self.to_le_bytes();
+        state.write(&bytes[..len])
+    }
+}
+
+/// Bus event as seen by a trace parser or a bus model, error and overload frames carry no ID.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum FrameKind {
+    Data(FrameId),
+    Error,
+    Overload,
+}
+impl FrameKind {
+    pub fn id(&self) -> Option<FrameId> {
+        match self {
+            FrameKind::Data(id) => Some(*id),
+            FrameKind::Error | FrameKind::Overload => None
         }
     }
+
+    pub fn is_data(&self) -> bool {
+        matches!(self, FrameKind::Data(_))
+    }
+}
+impl From<FrameId> for FrameKind {
+    fn from(id: FrameId) -> Self {
+        FrameKind::Data(id)
+    }
 }
 
 // #[cfg(test)]
@@ -168,4 +428,150 @@ mod tests {
         assert_eq!(eid0 > sid0, true);
         assert_eq!(sid7 < eid0, true);
     }
+
+    #[test]
+    fn check_display() {
+        extern crate std;
+        use std::format;
+        let sid = StandardId::new(0x1A).unwrap();
+        let eid = ExtendedId::new(0x12A).unwrap();
+        assert_eq!(format!("{}", sid), "0x01A");
+        assert_eq!(format!("{:X}", sid), "01A");
+        assert_eq!(format!("{:#X}", sid), "0x01A");
+        assert_eq!(format!("{:x}", sid), "01a");
+        assert_eq!(format!("{:#x}", sid), "0x01a");
+        assert_eq!(format!("{}", eid), "0x0000012A");
+        assert_eq!(format!("{:X}", eid), "0000012A");
+        assert_eq!(format!("{:#X}", eid), "0x0000012A");
+        assert_eq!(format!("{:x}", eid), "0000012a");
+        assert_eq!(format!("{:#x}", eid), "0x0000012a");
+        assert_eq!(format!("{:?}", FrameId::Standard(sid)), "FrameId(0x01A)");
+    }
+
+    #[test]
+    fn check_const_cmp() {
+        const SID: FrameId = FrameId::new_standard_or_panic(0x10);
+        const EID: FrameId = FrameId::new_extended_or_panic(0x10);
+        const ROUTES: [(FrameId, u8); 2] = [(SID, 1), (EID, 2)];
+        const fn route(id: FrameId) -> u8 {
+            let mut i = 0;
+            while i < ROUTES.len() {
+                if ROUTES[i].0.eq_const(&id) {
+                    return ROUTES[i].1;
+                }
+                i += 1;
+            }
+            0
+        }
+        const EID_ROUTE: u8 = route(EID);
+        const SID_EID: i8 = SID.cmp_const(&EID);
+        assert_eq!(EID_ROUTE, 2);
+        assert_eq!(SID_EID, -1);
+        let ids = [SID, EID, FrameId::new_standard(0x11).unwrap(), FrameId::new_extended(0x1).unwrap()];
+        for l in ids.iter() {
+            for r in ids.iter() {
+                assert_eq!(l.cmp_const(r).cmp(&0), l.cmp(r));
+                assert_eq!(l.eq_const(r), l == r);
+            }
+        }
+    }
+
+    #[test]
+    fn check_from_raw_auto() {
+        assert_eq!(FrameId::from_raw_auto(0x7FF), FrameId::new_standard(0x7FF));
+        assert_eq!(FrameId::from_raw_auto(0x800), FrameId::new_extended(0x800));
+        assert_eq!(FrameId::from_raw_auto(0x1FFFFFFF), FrameId::new_extended(0x1FFFFFFF));
+        assert_eq!(FrameId::from_raw_auto(0x20000000), None);
+        assert_eq!(FrameId::from_raw_auto_ext(0x1), FrameId::new_extended(0x1));
+    }
+
+    #[test]
+    fn check_base_id() {
+        assert_eq!(FrameId::new_standard(0x7FF).unwrap().base_id(), 0x7FF);
+        assert_eq!(FrameId::new_extended(0x1FFFFFFF).unwrap().base_id(), 0x7FF);
+        assert_eq!(FrameId::new_extended(0x0003FFFF).unwrap().base_id(), 0);
+        assert_eq!(FrameId::new_extended(0x123 << 18).unwrap().base_id(), 0x123);
+    }
+
+    #[test]
+    fn check_id_consts() {
+        assert_eq!(StandardId::new(0x7FF), Some(StandardId::MAX));
+        assert_eq!(StandardId::new(0x800), None);
+        assert_eq!(ExtendedId::new(0x1FFFFFFF), Some(ExtendedId::MAX));
+        assert_eq!(ExtendedId::new(0x20000000), None);
+        assert_eq!(StandardId::ZERO.inner(), 0);
+        assert_eq!(ExtendedId::ZERO.inner(), 0);
+        assert_eq!(FrameId::broadcast_standard(), FrameId::new_standard(0x7FF).unwrap());
+        assert_eq!(FrameId::broadcast_extended(), FrameId::new_extended(0x1FFFFFFF).unwrap());
+    }
+
+    #[test]
+    fn check_ranges() {
+        assert!(!FrameId::new_standard(0x7FF).unwrap().is_reserved_j1939());
+        assert!(!FrameId::new_extended(0x18FEF100).unwrap().is_reserved_j1939());
+        assert!(FrameId::new_extended(0x1AFEF100).unwrap().is_reserved_j1939());
+
+        let sid = FrameId::new_standard(0x80).unwrap();
+        assert!(sid.is_in_range(0x80, 0xFF));
+        assert!(sid.is_in_range(0x0, 0x80));
+        assert!(!sid.is_in_range(0x81, 0xFF));
+        assert!(FrameId::new_extended(0x80).unwrap().is_in_range(0x80, 0x80));
+    }
+
+    #[test]
+    fn check_canopen() {
+        // TPDO1 of node 5
+        let id = StandardId::from_canopen(0x3, 0x05).unwrap();
+        assert_eq!(id.inner(), 0x185);
+        assert_eq!(id.function_code(), 0x3);
+        assert_eq!(id.node_id(), 0x05);
+        assert_eq!(StandardId::from_canopen(0xF, 0x7F), Some(StandardId::MAX));
+        assert_eq!(StandardId::from_canopen(0x10, 0x05), None);
+        assert_eq!(StandardId::from_canopen(0x3, 0x80), None);
+    }
+
+    #[test]
+    fn check_j1939_pdu1() {
+        let id = ExtendedId::from_j1939_pdu1(6, 0xEA, 0x25, 0xF9).unwrap();
+        assert_eq!(id.inner(), 0x18EA25F9);
+        assert_eq!(id.destination_address(), Some(0x25));
+        assert_eq!(ExtendedId::from_j1939_pdu1(8, 0xEA, 0x25, 0xF9), None);
+        assert_eq!(ExtendedId::from_j1939_pdu1(6, 0xF0, 0x25, 0xF9), None);
+        assert_eq!(ExtendedId::new(0x18FEF100).unwrap().destination_address(), None);
+    }
+
+    #[test]
+    fn check_eq_raw() {
+        let sid = FrameId::new_standard(0x123).unwrap();
+        let eid = FrameId::new_extended(0x123).unwrap();
+        assert!(sid.eq_raw(0x123, false));
+        assert!(!sid.eq_raw(0x123, true));
+        assert!(!sid.eq_raw(0x10123, false));
+        assert!(eid.eq_raw(0x123, true));
+        assert!(!eid.eq_raw(0x123, false));
+    }
+
+    #[test]
+    fn check_id_bytes() {
+        let sid = FrameId::new_standard(0x123).unwrap();
+        assert_eq!(sid.to_be_bytes(), ([0x01, 0x23, 0x00, 0x00], 2));
+        assert_eq!(sid.to_le_bytes(), ([0x23, 0x01, 0x00, 0x00], 2));
+        let eid = FrameId::new_extended(0x1234567).unwrap();
+        assert_eq!(eid.to_be_bytes(), ([0x01, 0x23, 0x45, 0x67], 4));
+        assert_eq!(eid.to_le_bytes(), ([0x67, 0x45, 0x23, 0x01], 4));
+    }
+
+    #[test]
+    fn check_const_ids() {
+        const SID: StandardId = StandardId::new_or_panic(0x7FF);
+        const EID: FrameId = FrameId::new_extended_or_panic(0x1FFFFFFF);
+        assert_eq!(SID.inner(), 0x7FF);
+        assert_eq!(EID, FrameId::new_extended(0x1FFFFFFF).unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    fn check_const_ids_out_of_range() {
+        let _ = StandardId::new_or_panic(0x800);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+use crate::{Frame, FrameRef, FrameId};
+use crate::heap::{Heap, MarkerTraits, GroupTraits};
+use heapless::FnvIndexMap;
+
+/// [Heap] with an index of queued frame IDs, making ID lookups O(1) instead of a scan over the whole heap.
+/// Index costs K entries of memory, K must be a power of two and not less than N, so that there is room
+/// for every queued frame to have a distinct ID.
+/// Only counts per ID are stored, so the index is not affected by sorting.
+pub struct IndexedHeap<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize, const K: usize> {
+    heap: Heap<M, G, MTU, N>,
+    /// Number of queued frames per ID
+    index: FnvIndexMap<FrameId, usize, K>,
+}
+
+impl<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize, const K: usize> IndexedHeap<M, G, MTU, N, K> {
+    pub fn new(heap: Heap<M, G, MTU, N>) -> Self {
+        assert!(K >= N, "index must have room for N distinct IDs");
+        let mut indexed = IndexedHeap {
+            heap,
+            index: FnvIndexMap::new()
+        };
+        indexed.rebuild();
+        indexed
+    }
+
+    pub fn push(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<usize, Frame<MTU>> {
+        let replaced = self.heap.push(frame, marker, group)?;
+        self.pushed(frame.id, replaced);
+        Ok(replaced)
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn push_at(&mut self, frame: Frame<MTU>, marker: M, group: G, ts: u32) -> Result<usize, Frame<MTU>> {
+        let replaced = self.heap.push_at(frame, marker, group, ts)?;
+        self.pushed(frame.id, replaced);
+        Ok(replaced)
+    }
+
+    pub fn pop(&mut self) -> Option<(Frame<MTU>, M)> {
+        let popped = self.heap.pop()?;
+        self.remove(popped.0.id);
+        Some(popped)
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn pop_with_timestamp(&mut self) -> Option<(Frame<MTU>, M, u32)> {
+        let popped = self.heap.pop_with_timestamp()?;
+        self.remove(popped.0.id);
+        Some(popped)
+    }
+
+    pub fn contains_id(&self, id: FrameId) -> bool {
+        self.index.contains_key(&id)
+    }
+
+    /// Same as [Heap::contains], but only scans the heap if a frame with the same ID is queued.
+    pub fn contains(&self, frame: &FrameRef) -> bool {
+        self.contains_id(frame.id) && self.heap.contains(frame)
+    }
+
+    /// Removes all frames with the given ID, returning how many were removed.
+    pub fn remove_id(&mut self, id: FrameId) -> usize {
+        let count = match self.index.remove(&id) {
+            Some(count) => count,
+            None => return 0
+        };
+        self.heap.drain_filter(|frame, _| frame.id == id, &mut |_, _| {});
+        count
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.index.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.len() == 0
+    }
+
+    pub fn heap(&self) -> &Heap<M, G, MTU, N> {
+        &self.heap
+    }
+
+    pub fn into_inner(self) -> Heap<M, G, MTU, N> {
+        self.heap
+    }
+
+    /// Evictions can remove frames of any ID, so the index is rebuilt after them.
+    fn pushed(&mut self, id: FrameId, replaced: usize) {
+        if replaced == 0 {
+            self.add(id);
+        } else {
+            self.rebuild();
+        }
+    }
+
+    fn add(&mut self, id: FrameId) {
+        match self.index.get_mut(&id) {
+            Some(count) => *count += 1,
+            None => {
+                // can't fail, since K >= N
+                let _ = self.index.insert(id, 1);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: FrameId) {
+        if let Some(count) = self.index.get_mut(&id) {
+            *count -= 1;
+            if *count == 0 {
+                self.index.remove(&id);
+            }
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.index.clear();
+        for frame in self.heap.frames() {
+            match self.index.get_mut(&frame.id) {
+                Some(count) => *count += 1,
+                None => {
+                    let _ = self.index.insert(frame.id, 1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heap::SortOn;
+
+    #[test]
+    fn check_index() {
+        let a = Frame::new(FrameId::new_extended(0x10).unwrap(), &[1]).unwrap();
+        let b = Frame::new(FrameId::new_extended(0x20).unwrap(), &[2]).unwrap();
+        let c = Frame::new(FrameId::new_extended(0x30).unwrap(), &[3]).unwrap();
+        let mut heap = IndexedHeap::<(), u8, 8, 4, 4>::new(Heap::new(SortOn::Push));
+        assert_eq!(heap.push(b, (), 0), Ok(0));
+        assert_eq!(heap.push(c, (), 1), Ok(0));
+        assert_eq!(heap.push(b, (), 2), Ok(0));
+        assert_eq!(heap.push(c, (), 3), Ok(0));
+        assert!(heap.contains(&b.as_frame_ref()));
+        assert!(!heap.contains_id(a.id));
+
+        // evicts c
+        assert_eq!(heap.push(a, (), 4), Ok(1));
+        assert!(heap.contains_id(a.id));
+        assert!(heap.contains_id(c.id));
+        assert_eq!(heap.pop(), Some((a, ())));
+        assert!(!heap.contains_id(a.id));
+
+        assert_eq!(heap.remove_id(b.id), 2);
+        assert_eq!(heap.remove_id(b.id), 0);
+        assert!(!heap.contains(&b.as_frame_ref()));
+        assert_eq!(heap.len(), 1);
+        assert_eq!(heap.pop(), Some((c, ())));
+        assert!(!heap.contains_id(c.id));
+        assert!(heap.is_empty());
+    }
+}
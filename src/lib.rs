@@ -3,16 +3,29 @@
 pub mod id;
 pub mod frame;
 pub mod heap;
+pub mod wire;
+pub mod bitstream;
+pub mod scheduler;
+#[cfg(feature = "timestamp")]
+pub mod timed;
+pub mod traced;
+#[cfg(feature = "index")]
+pub mod indexed;
 
-pub use id::FrameId;
+pub use id::{FrameId, FrameKind};
 pub use frame::{Frame, FrameRef};
+pub use wire::FrameDecoder;
 
 #[cfg(feature = "serialization")]
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Error {
-    WrongLength
+    WrongLength,
+    BufferTooSmall,
+    InvalidId,
+    InvalidHex,
+    UnexpectedEnd,
 }
 
 pub const EXTENDED_ID_ALL_BITS: u32 = 0x1FFFFFFF;
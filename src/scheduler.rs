@@ -0,0 +1,90 @@
+use crate::Frame;
+use crate::heap::{Heap, MarkerTraits, GroupTraits};
+
+/// Paces frames from a [Heap] as a single CAN channel would: next frame is released only after the
+/// previous one finished transmitting, based on it's worst case bit count and bus bitrate.
+/// Time is in microseconds and is allowed to wrap, but [next](Scheduler::next) must be called
+/// at least once every 2^31 us, so that wrapping comparison stays valid.
+pub struct Scheduler<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> {
+    heap: Heap<M, G, MTU, N>,
+    bitrate: u32,
+    busy_until: Option<u32>,
+}
+
+impl<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> Scheduler<M, G, MTU, N> {
+    /// bitrate is in bits per second, panics if it is 0.
+    pub fn new(heap: Heap<M, G, MTU, N>, bitrate: u32) -> Self {
+        assert!(bitrate != 0, "bitrate must not be 0");
+        Scheduler {
+            heap,
+            bitrate,
+            busy_until: None
+        }
+    }
+
+    /// Pops the next frame if the previous one finished transmitting by now_us.
+    pub fn next(&mut self, now_us: u32) -> Option<(Frame<MTU>, M)> {
+        if !self.is_idle(now_us) {
+            return None;
+        }
+        self.busy_until = None;
+        let (frame, marker) = self.heap.pop()?;
+        self.busy_until = Some(now_us.wrapping_add(self.transmission_time_us(&frame)));
+        Some((frame, marker))
+    }
+
+    pub fn is_idle(&self, now_us: u32) -> bool {
+        match self.busy_until {
+            Some(busy_until) => (now_us.wrapping_sub(busy_until) as i32) >= 0,
+            None => true
+        }
+    }
+
+    /// Worst case time on the bus, rounded up to the next microsecond.
+    pub fn transmission_time_us(&self, frame: &Frame<MTU>) -> u32 {
+        let bits = frame.max_bit_count() as u64;
+        let bitrate = self.bitrate as u64;
+        (bits * 1_000_000).div_ceil(bitrate) as u32
+    }
+
+    pub fn heap(&self) -> &Heap<M, G, MTU, N> {
+        &self.heap
+    }
+
+    pub fn heap_mut(&mut self) -> &mut Heap<M, G, MTU, N> {
+        &mut self.heap
+    }
+
+    pub fn into_inner(self) -> Heap<M, G, MTU, N> {
+        self.heap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameId;
+    use crate::heap::SortOn;
+
+    #[test]
+    #[should_panic]
+    fn check_zero_bitrate() {
+        let _ = Scheduler::new(Heap::<(), u8, 8, 4>::new(SortOn::Push), 0);
+    }
+
+    #[test]
+    fn check_pacing() {
+        let mut scheduler = Scheduler::new(Heap::<(), u8, 8, 4>::new(SortOn::Push), 500_000);
+        let frame = Frame::new(FrameId::new_standard(0x123).unwrap(), &[0; 8]).unwrap();
+        assert_eq!(scheduler.transmission_time_us(&frame), 270);
+        assert_eq!(scheduler.heap_mut().push(frame, (), 0), Ok(0));
+        assert_eq!(scheduler.heap_mut().push(frame, (), 0), Ok(0));
+
+        let now = u32::MAX - 100;
+        assert_eq!(scheduler.next(now), Some((frame, ())));
+        assert_eq!(scheduler.next(now.wrapping_add(269)), None);
+        assert_eq!(scheduler.next(now.wrapping_add(270)), Some((frame, ())));
+        assert_eq!(scheduler.next(now.wrapping_add(1000)), None);
+        assert!(scheduler.is_idle(now.wrapping_add(1000)));
+    }
+}
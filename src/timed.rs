@@ -0,0 +1,74 @@
+use crate::Frame;
+use crate::heap::{Heap, MarkerTraits, GroupTraits};
+
+/// Timed-release queue: each frame is held until it's release time, frames are released in the
+/// order of release time and then priority. Built on top of [Heap] storage, so the same
+/// overflow rules apply when it's full.
+/// Time is allowed to wrap, but release times must be within 2^31 of the current time.
+pub struct TimedHeap<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> {
+    heap: Heap<M, G, MTU, N>,
+}
+
+impl<M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> TimedHeap<M, G, MTU, N> {
+    pub fn new(heap: Heap<M, G, MTU, N>) -> Self {
+        TimedHeap {
+            heap
+        }
+    }
+
+    pub fn push(&mut self, frame: Frame<MTU>, marker: M, group: G, release_at: u32) -> Result<usize, Frame<MTU>> {
+        self.heap.push_at(frame, marker, group, release_at)
+    }
+
+    /// Earliest due frame, which release time is not later than now, or None if nothing is due yet.
+    pub fn pop_due(&mut self, now: u32) -> Option<(Frame<MTU>, M)> {
+        self.heap.pop_due(now).map(|(frame, marker, _)| (frame, marker))
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.len() == 0
+    }
+
+    pub fn heap(&self) -> &Heap<M, G, MTU, N> {
+        &self.heap
+    }
+
+    pub fn into_inner(self) -> Heap<M, G, MTU, N> {
+        self.heap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameId;
+    use crate::heap::SortOn;
+
+    #[test]
+    fn check_release_order() {
+        let mut heap = TimedHeap::new(Heap::<u8, u8, 8, 8>::new(SortOn::Pop));
+        let lower_prio = Frame::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        let higher_prio = Frame::new(FrameId::new_extended(0x12).unwrap(), &[]).unwrap();
+        let t0 = u32::MAX - 10;
+        assert_eq!(heap.push(higher_prio, 0, 0, t0.wrapping_add(20)), Ok(0));
+        assert_eq!(heap.push(lower_prio, 1, 0, t0.wrapping_add(10)), Ok(0));
+        assert_eq!(heap.push(lower_prio, 2, 0, t0.wrapping_add(20)), Ok(0));
+        assert_eq!(heap.push(higher_prio, 3, 0, t0.wrapping_add(30)), Ok(0));
+
+        assert_eq!(heap.pop_due(t0), None);
+        assert_eq!(heap.pop_due(t0.wrapping_add(25)), Some((lower_prio, 1)));
+        assert_eq!(heap.pop_due(t0.wrapping_add(25)), Some((higher_prio, 0)));
+        assert_eq!(heap.pop_due(t0.wrapping_add(25)), Some((lower_prio, 2)));
+        assert_eq!(heap.pop_due(t0.wrapping_add(25)), None);
+        assert_eq!(heap.pop_due(t0.wrapping_add(30)), Some((higher_prio, 3)));
+        assert!(heap.is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{Frame, FrameId, FrameRef};
+use crate::id::StandardId;
+use crate::heap::{Heap, MarkerTraits, GroupTraits, Seq, MergeEvent, Timestamp, Deadline, NO_TIMESTAMP, NO_DEADLINE};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum TraceOp {
+    Push,
+    Pop,
+    /// Frame was dropped without being popped: removed to make room for another one, overwritten by
+    /// [push_coalesce](TracedHeap::push_coalesce) or cleared.
+    Evict,
+    /// Frame was not accepted, seq is the one it would have gotten.
+    Reject,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct TraceEvent {
+    pub op: TraceOp,
+    pub id: FrameId,
+    pub seq: Seq,
+}
+
+impl Default for TraceEvent {
+    /// Placeholder to initialize the ring buffer with, never returned by [events](TracedHeap::events).
+    fn default() -> Self {
+        TraceEvent {
+            op: TraceOp::Push,
+            id: FrameId::Standard(StandardId::ZERO),
+            seq: 0,
+        }
+    }
+}
+
+/// [Heap] wrapper that records every push, pop, eviction and rejection into a caller provided ring buffer,
+/// overwriting the oldest events when it's full. Frames given out by [drain_filter](TracedHeap::drain_filter)
+/// are recorded as popped.
+///
+/// Operations that don't add or remove frames, such as [update_markers](TracedHeap::update_markers) and
+/// [compact](TracedHeap::compact), are forwarded without recording anything.
+pub struct TracedHeap<'a, M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> {
+    heap: Heap<M, G, MTU, N>,
+    ring: &'a mut [TraceEvent],
+    recorded: usize,
+}
+
+impl<'a, M: MarkerTraits, G: GroupTraits, const MTU: usize, const N: usize> TracedHeap<'a, M, G, MTU, N> {
+    pub fn new(heap: Heap<M, G, MTU, N>, ring: &'a mut [TraceEvent]) -> Self {
+        TracedHeap {
+            heap,
+            ring,
+            recorded: 0,
+        }
+    }
+
+    pub fn push(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<usize, Frame<MTU>> {
+        self.push_traced(frame, marker, group, NO_TIMESTAMP, NO_DEADLINE)
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn push_at(&mut self, frame: Frame<MTU>, marker: M, group: G, ts: u32) -> Result<usize, Frame<MTU>> {
+        self.push_traced(frame, marker, group, ts, NO_DEADLINE)
+    }
+
+    #[cfg(feature = "deadline")]
+    pub fn push_with_deadline(&mut self, frame: Frame<MTU>, marker: M, group: G, deadline: u32) -> Result<usize, Frame<MTU>> {
+        self.push_traced(frame, marker, group, NO_TIMESTAMP, Some(deadline))
+    }
+
+    /// Nothing is recorded if an equal frame is already queued, since the heap is left unchanged.
+    pub fn push_dedup(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<Option<usize>, Frame<MTU>> {
+        if self.heap.contains(&frame.as_frame_ref()) {
+            return Ok(None);
+        }
+        self.push(frame, marker, group).map(Some)
+    }
+
+    fn push_traced(
+        &mut self,
+        frame: Frame<MTU>,
+        marker: M,
+        group: G,
+        ts: Timestamp,
+        deadline: Deadline
+    ) -> Result<usize, Frame<MTU>> {
+        let seq = self.heap.current_seq();
+        let TracedHeap { heap, ring, recorded } = self;
+        let result = heap.push_at_with(frame, marker, group, ts, deadline, |id, seq| {
+            Self::record_into(ring, recorded, TraceOp::Evict, id, seq);
+        });
+        let op = if result.is_ok() { TraceOp::Push } else { TraceOp::Reject };
+        self.record(op, frame.id, seq);
+        result
+    }
+
+    pub fn push_saturating(&mut self, frame: Frame<MTU>, marker: M, group: G) -> bool {
+        let seq = self.heap.current_seq();
+        let pushed = self.heap.push_saturating(frame, marker, group);
+        let op = if pushed { TraceOp::Push } else { TraceOp::Reject };
+        self.record(op, frame.id, seq);
+        pushed
+    }
+
+    /// Overwritten frame is recorded as evicted, followed by a push of the new one.
+    pub fn push_coalesce(&mut self, frame: Frame<MTU>, marker: M, group: G) -> Result<Option<usize>, Frame<MTU>> {
+        let existing = self.heap.iter_with_seq()
+            .find(|(queued, _, _)| queued.id == frame.id)
+            .map(|(_, _, seq)| seq);
+        match existing {
+            Some(old_seq) => {
+                let seq = self.heap.current_seq();
+                let result = self.heap.push_coalesce(frame, marker, group);
+                self.record(TraceOp::Evict, frame.id, old_seq);
+                self.record(TraceOp::Push, frame.id, seq);
+                result
+            }
+            None => self.push(frame, marker, group).map(Some)
+        }
+    }
+
+    pub fn merge<const N2: usize>(&mut self, other: &mut Heap<M, G, MTU, N2>) -> usize {
+        let TracedHeap { heap, ring, recorded } = self;
+        heap.merge_with(other, |event, id, seq| {
+            let op = match event {
+                MergeEvent::Pushed => TraceOp::Push,
+                MergeEvent::Evicted => TraceOp::Evict,
+                MergeEvent::Rejected => TraceOp::Reject,
+            };
+            Self::record_into(ring, recorded, op, id, seq);
+        })
+    }
+
+    pub fn pop(&mut self) -> Option<(Frame<MTU>, M)> {
+        let (frame, marker, _, seq) = self.heap.pop_with_seq()?;
+        self.record(TraceOp::Pop, frame.id, seq);
+        Some((frame, marker))
+    }
+
+    #[cfg(feature = "timestamp")]
+    pub fn pop_with_timestamp(&mut self) -> Option<(Frame<MTU>, M, u32)> {
+        let (frame, marker, ts, seq) = self.heap.pop_with_seq()?;
+        self.record(TraceOp::Pop, frame.id, seq);
+        Some((frame, marker, ts))
+    }
+
+    pub fn pop_if(&mut self, pred: impl FnOnce(&FrameRef, &M) -> bool) -> Option<(Frame<MTU>, M)> {
+        let (frame, marker, seq) = self.heap.pop_if_with_seq(pred)?;
+        self.record(TraceOp::Pop, frame.id, seq);
+        Some((frame, marker))
+    }
+
+    pub fn pop_into(&mut self, out: &mut [(Frame<MTU>, M)]) -> usize {
+        let TracedHeap { heap, ring, recorded } = self;
+        heap.pop_into_with(out, |id, seq| Self::record_into(ring, recorded, TraceOp::Pop, id, seq))
+    }
+
+    /// Recorded events from the oldest to the newest one, only the last ring.len() are kept.
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        let len = self.ring.len();
+        let kept = self.recorded.min(len);
+        let start = if len == 0 { 0 } else { (self.recorded - kept) % len };
+        self.ring.iter().cycle().skip(start).take(kept)
+    }
+
+    /// Total number of events recorded, including the ones already overwritten.
+    pub fn recorded(&self) -> usize {
+        self.recorded
+    }
+
+    pub fn drain_filter(
+        &mut self,
+        pred: impl FnMut(&FrameRef, &M) -> bool,
+        out: &mut impl FnMut(Frame<MTU>, M)
+    ) {
+        let TracedHeap { heap, ring, recorded } = self;
+        heap.drain_filter_with(pred, &mut |frame, marker, seq| {
+            Self::record_into(ring, recorded, TraceOp::Pop, frame.id, seq);
+            out(frame, marker);
+        });
+    }
+
+    pub fn clear_matching(&mut self, pred: impl Fn(&M) -> bool) -> usize {
+        let TracedHeap { heap, ring, recorded } = self;
+        let mut removed = 0;
+        heap.drain_filter_with(|_, marker| pred(marker), &mut |frame, _, seq| {
+            Self::record_into(ring, recorded, TraceOp::Evict, frame.id, seq);
+            removed += 1;
+        });
+        removed
+    }
+
+    /// Cleared frames are recorded as evicted.
+    pub fn clear(&mut self) {
+        self.clear_matching(|_| true);
+    }
+
+    pub fn update_markers(&mut self, f: impl FnMut(&FrameRef, &mut M)) {
+        self.heap.update_markers(f);
+    }
+
+    pub fn compact(&mut self) {
+        self.heap.compact();
+    }
+
+    /// Queued frames get new seqs, so events recorded before no longer match them by seq.
+    pub fn renumber(&mut self) {
+        self.heap.renumber();
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.len() == 0
+    }
+
+    pub fn heap(&self) -> &Heap<M, G, MTU, N> {
+        &self.heap
+    }
+
+    pub fn into_inner(self) -> Heap<M, G, MTU, N> {
+        self.heap
+    }
+
+    fn record(&mut self, op: TraceOp, id: FrameId, seq: Seq) {
+        Self::record_into(self.ring, &mut self.recorded, op, id, seq);
+    }
+
+    fn record_into(ring: &mut [TraceEvent], recorded: &mut usize, op: TraceOp, id: FrameId, seq: Seq) {
+        if ring.is_empty() {
+            return;
+        }
+        ring[*recorded % ring.len()] = TraceEvent { op, id, seq };
+        *recorded += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heap::SortOn;
+
+    #[test]
+    fn check_trace() {
+        let mut ring = [TraceEvent::default(); 4];
+        let mut heap = TracedHeap::new(Heap::<(), u8, 8, 2>::new(SortOn::Pop), &mut ring);
+        let frame = |id| Frame::new(FrameId::new_standard(id).unwrap(), &[]).unwrap();
+        let event = |op, id, seq| TraceEvent { op, id: FrameId::new_standard(id).unwrap(), seq };
+        assert_eq!(heap.push(frame(0x300), (), 0), Ok(0));
+        assert_eq!(heap.push(frame(0x200), (), 1), Ok(0));
+        assert_eq!(heap.push(frame(0x400), (), 2), Err(frame(0x400)));
+        assert_eq!(heap.push(frame(0x100), (), 3), Ok(1));
+        assert_eq!(heap.pop(), Some((frame(0x100), ())));
+
+        assert_eq!(heap.recorded(), 6);
+        let mut events = heap.events();
+        assert_eq!(events.next(), Some(&event(TraceOp::Reject, 0x400, 2)));
+        assert_eq!(events.next(), Some(&event(TraceOp::Evict, 0x300, 0)));
+        assert_eq!(events.next(), Some(&event(TraceOp::Push, 0x100, 2)));
+        assert_eq!(events.next(), Some(&event(TraceOp::Pop, 0x100, 2)));
+        assert_eq!(events.next(), None);
+    }
+
+    #[test]
+    fn check_trace_other_ops() {
+        let mut ring = [TraceEvent::default(); 16];
+        let mut heap = TracedHeap::new(Heap::<u8, u8, 8, 2>::new(SortOn::Pop), &mut ring);
+        let frame = |id| Frame::new(FrameId::new_standard(id).unwrap(), &[]).unwrap();
+        let event = |op, id, seq| TraceEvent { op, id: FrameId::new_standard(id).unwrap(), seq };
+        assert!(heap.push_saturating(frame(0x300), 0, 0));
+        assert_eq!(heap.push_coalesce(frame(0x300), 1, 0), Ok(None));
+        assert_eq!(heap.push_coalesce(frame(0x200), 2, 1), Ok(Some(0)));
+        assert!(!heap.push_saturating(frame(0x100), 3, 2));
+
+        let mut other = Heap::<u8, u8, 8, 2>::new(SortOn::Pop);
+        assert_eq!(other.push(frame(0x100), 4, 3), Ok(0));
+        assert_eq!(heap.merge(&mut other), 1);
+
+        let mut drained = 0;
+        heap.drain_filter(|_, marker| *marker == 4, &mut |_, _| drained += 1);
+        assert_eq!(drained, 1);
+        assert_eq!(heap.clear_matching(|marker| *marker == 2), 1);
+        assert!(heap.is_empty());
+
+        let expected = [
+            event(TraceOp::Push, 0x300, 0),
+            event(TraceOp::Evict, 0x300, 0),
+            event(TraceOp::Push, 0x300, 1),
+            event(TraceOp::Push, 0x200, 2),
+            event(TraceOp::Reject, 0x100, 3),
+            event(TraceOp::Evict, 0x300, 1),
+            event(TraceOp::Push, 0x100, 3),
+            event(TraceOp::Pop, 0x100, 3),
+            event(TraceOp::Evict, 0x200, 2),
+        ];
+        assert!(heap.events().eq(expected.iter()));
+    }
+
+    #[test]
+    fn check_trace_pops() {
+        let mut ring = [TraceEvent::default(); 8];
+        let mut heap = TracedHeap::new(Heap::<u8, u8, 8, 4>::new(SortOn::Pop), &mut ring);
+        let frame = |id| Frame::new(FrameId::new_standard(id).unwrap(), &[]).unwrap();
+        let event = |op, id, seq| TraceEvent { op, id: FrameId::new_standard(id).unwrap(), seq };
+        for (i, id) in [0x300, 0x200, 0x100].iter().enumerate() {
+            assert_eq!(heap.push(frame(*id), i as u8, 0), Ok(0));
+        }
+        assert_eq!(heap.push_dedup(frame(0x100), 3, 0), Ok(None));
+        heap.update_markers(|_, marker| *marker += 10);
+        assert_eq!(heap.pop_if(|_, marker| *marker == 10), None);
+        assert_eq!(heap.pop_if(|_, marker| *marker == 12), Some((frame(0x100), 12)));
+        heap.compact();
+        let mut out = [(frame(0), 0); 4];
+        assert_eq!(heap.pop_into(&mut out), 2);
+        assert_eq!(&out[..2], &[(frame(0x200), 11), (frame(0x300), 10)]);
+
+        let expected = [
+            event(TraceOp::Push, 0x300, 0),
+            event(TraceOp::Push, 0x200, 1),
+            event(TraceOp::Push, 0x100, 2),
+            event(TraceOp::Pop, 0x100, 2),
+            event(TraceOp::Pop, 0x200, 1),
+            event(TraceOp::Pop, 0x300, 0),
+        ];
+        assert!(heap.events().eq(expected.iter()));
+    }
+}
@@ -0,0 +1,130 @@
+//! Length-prefixed binary format for passing frames over serial links (UART, USB CAN adapters):
+//! ```text
+//! | id: u32 BE, bit 31 set for extended | len: u8 | data: [u8; len] |
+//! ```
+use crate::{Frame, FrameId, Error};
+use crate::id::{StandardId, ExtendedId};
+
+const EXTENDED_FLAG: u32 = 1 << 31;
+const HEADER_LEN: usize = 5;
+
+impl<const MTU: usize> Frame<MTU> {
+    /// Number of bytes this frame occupies in wire format.
+    pub fn wire_len(&self) -> usize {
+        HEADER_LEN + self.data().len()
+    }
+
+    /// Serialize frame into buf, returning the number of bytes written.
+    /// Frames longer than 255 bytes don't fit into the length field, WrongLength is returned for them.
+    pub fn to_wire(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.data().len() > u8::MAX as usize {
+            return Err(Error::WrongLength);
+        }
+        let len = self.wire_len();
+        if buf.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+        let raw_id = match self.id {
+            FrameId::Standard(sid) => sid.inner() as u32,
+            FrameId::Extended(eid) => eid.inner() | EXTENDED_FLAG
+        };
+        buf[0..4].copy_from_slice(&raw_id.to_be_bytes());
+        buf[4] = self.data().len() as u8;
+        buf[HEADER_LEN..len].copy_from_slice(self.data());
+        Ok(len)
+    }
+}
+
+/// Lazily decodes frames from a byte stream in wire format.
+/// Trailing partial frame is not consumed, iteration stops and [remaining](FrameDecoder::remaining) bytes
+/// can be carried over to the next chunk of the stream.
+pub struct FrameDecoder<'a, const MTU: usize> {
+    buf: &'a [u8],
+}
+impl<'a, const MTU: usize> FrameDecoder<'a, MTU> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        FrameDecoder {
+            buf
+        }
+    }
+
+    /// Number of bytes not yet consumed.
+    pub fn remaining(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Bytes not yet consumed.
+    pub fn remainder(&self) -> &'a [u8] {
+        self.buf
+    }
+}
+impl<'a, const MTU: usize> Iterator for FrameDecoder<'a, MTU> {
+    type Item = Result<Frame<MTU>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.len() < HEADER_LEN {
+            return None;
+        }
+        let len = HEADER_LEN + self.buf[4] as usize;
+        if self.buf.len() < len {
+            return None;
+        }
+        let (frame_bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+
+        let raw_id = u32::from_be_bytes([frame_bytes[0], frame_bytes[1], frame_bytes[2], frame_bytes[3]]);
+        let id = if raw_id & EXTENDED_FLAG != 0 {
+            ExtendedId::new(raw_id & !EXTENDED_FLAG).map(FrameId::Extended)
+        } else if raw_id <= u16::MAX as u32 {
+            StandardId::new(raw_id as u16).map(FrameId::Standard)
+        } else {
+            None
+        };
+        let id = match id {
+            Some(id) => id,
+            None => return Some(Err(Error::InvalidId))
+        };
+        Some(Frame::new(id, &frame_bytes[HEADER_LEN..]).ok_or(Error::WrongLength))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_round_trip() {
+        let f1 = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[1, 2, 3]).unwrap();
+        let f2 = Frame::<8>::new(FrameId::new_extended(0x123).unwrap(), &[]).unwrap();
+        let mut buf = [0u8; 32];
+        let mut len = f1.to_wire(&mut buf).unwrap();
+        len += f2.to_wire(&mut buf[len..]).unwrap();
+        assert_eq!(len, 13);
+        assert_eq!(&buf[..8], &[0x00, 0x00, 0x01, 0x23, 3, 1, 2, 3]);
+        assert_eq!(&buf[8..13], &[0x80, 0x00, 0x01, 0x23, 0]);
+
+        // partial trailing frame
+        let mut decoder = FrameDecoder::<8>::new(&buf[..len + 3]);
+        assert_eq!(decoder.next(), Some(Ok(f1)));
+        assert_eq!(decoder.next(), Some(Ok(f2)));
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.remaining(), 3);
+    }
+
+    #[test]
+    fn check_errors() {
+        let f = Frame::<8>::new(FrameId::new_standard(0x123).unwrap(), &[1, 2, 3]).unwrap();
+        assert_eq!(f.to_wire(&mut [0u8; 7]), Err(Error::BufferTooSmall));
+        let long = Frame::<300>::new(FrameId::new_standard(0x123).unwrap(), &[0; 300]).unwrap();
+        assert_eq!(long.to_wire(&mut [0u8; 512]), Err(Error::WrongLength));
+        let longest = Frame::<300>::new(FrameId::new_standard(0x123).unwrap(), &[0; 255]).unwrap();
+        assert_eq!(longest.to_wire(&mut [0u8; 512]), Ok(260));
+
+        let buf = [0x00, 0x00, 0x08, 0x00, 0, 0x00, 0x00, 0x00, 0x01, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut decoder = FrameDecoder::<8>::new(&buf);
+        assert_eq!(decoder.next(), Some(Err(Error::InvalidId)));
+        assert_eq!(decoder.next(), Some(Err(Error::WrongLength)));
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.remaining(), 0);
+    }
+}